@@ -9,6 +9,28 @@ pub type Result<T> = core::result::Result<T, Error>;
 pub enum Error {
 	#[from(String, &String, &str)]
 	Custom(String),
+
+	// -- Tag
+	/// An opening tag without a matching closing tag.
+	UnclosedTag { name: String, start_idx: usize },
+	/// An opening tag without a close delimiter.
+	MalformedOpenTag { name: String, start_idx: usize },
+	/// A closing tag without a matching opening tag.
+	UnexpectedClosingTag { name: String, start_idx: usize },
+	/// A required attribute absent from the opening tag.
+	MissingAttr { tag_name: String, key: String },
+	/// An attribute whose value cannot be converted to the requested type.
+	InvalidAttr {
+		tag_name: String,
+		key: String,
+		value: String,
+		cause: String,
+	},
+	/// An element that cannot be deserialized into the requested type (e.g., a missing field).
+	InvalidElem { tag_name: String, cause: String },
+	/// An element whose markup does not extract back unchanged under any of the candidate fences,
+	/// typically because its content contains its closing tag.
+	RenderCollision { tag_name: String, fences: Vec<String> },
 	// -- Externals
 }
 
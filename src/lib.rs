@@ -3,8 +3,10 @@
 // region:    --- Modules
 
 mod error;
+mod line_index;
 
 pub use error::{Error, Result};
+pub use line_index::{LineIndex, TextPos};
 
 pub mod tag;
 
@@ -0,0 +1,71 @@
+//! Mapping of byte offsets to line and column positions.
+
+use serde::Serialize;
+
+/// A 1-based line and column position in a text.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct TextPos {
+	/// The line number, starting at 1.
+	pub line: usize,
+
+	/// The column in UTF-8 bytes from the start of the line, starting at 1.
+	pub col_utf8: usize,
+
+	/// The column in UTF-16 code units from the start of the line, starting at 1 (e.g., for LSP clients).
+	pub col_utf16: usize,
+}
+
+/// An index of the line starts of a text, resolving byte offsets (e.g., `TagSpan` bounds) to `TextPos` values.
+///
+/// Lines end after each `\n`, so a `\r` of a `\r\n` ending counts as the last column of its line.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+	text: &'a str,
+	/// The byte offset of the first character of each line.
+	line_starts: Vec<usize>,
+}
+
+/// Constructors
+impl<'a> LineIndex<'a> {
+	/// Creates a new `LineIndex` for the text, in one pass over its bytes.
+	pub fn new(text: &'a str) -> Self {
+		let line_starts = std::iter::once(0)
+			.chain(text.bytes().enumerate().filter(|(_, b)| *b == b'\n').map(|(idx, _)| idx + 1))
+			.collect();
+
+		LineIndex { text, line_starts }
+	}
+}
+
+/// Accessors
+impl LineIndex<'_> {
+	/// Returns the number of lines, which is one more than the number of `\n` characters.
+	pub fn line_count(&self) -> usize {
+		self.line_starts.len()
+	}
+
+	/// Returns the position of the byte offset.
+	///
+	/// An offset past the end of the text resolves to the end of the text, and an offset inside
+	/// a multi-byte character resolves to the start of that character.
+	pub fn pos(&self, offset: usize) -> TextPos {
+		let offset = self.text.floor_char_boundary(offset);
+		let line = self.line_starts.partition_point(|&line_start| line_start <= offset);
+		let line_start = self.line_starts[line - 1];
+		let col_utf16 = self.text[line_start..offset].encode_utf16().count() + 1;
+
+		TextPos {
+			line,
+			col_utf8: offset - line_start + 1,
+			col_utf16,
+		}
+	}
+}
+
+// region:    --- Tests
+
+#[path = "line_index_tests.rs"]
+#[cfg(test)]
+mod tests;
+
+// endregion: --- Tests
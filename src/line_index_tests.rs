@@ -0,0 +1,48 @@
+//! Tests for `LineIndex`.
+
+use super::{LineIndex, TextPos};
+
+type Result<T> = core::result::Result<T, Box<dyn std::error::Error>>;
+
+fn pos(line: usize, col_utf8: usize, col_utf16: usize) -> TextPos {
+	TextPos {
+		line,
+		col_utf8,
+		col_utf16,
+	}
+}
+
+#[test]
+fn test_line_index_pos_lines_and_columns() -> Result<()> {
+	// -- Setup & Fixtures
+	let text = "ab\r\ncd\n\nef";
+	let index = LineIndex::new(text);
+
+	// -- Exec & Check
+	assert_eq!(index.line_count(), 4);
+	assert_eq!(index.pos(0), pos(1, 1, 1));
+	assert_eq!(index.pos(2), pos(1, 3, 3));
+	assert_eq!(index.pos(4), pos(2, 1, 1));
+	assert_eq!(index.pos(7), pos(3, 1, 1));
+	assert_eq!(index.pos(9), pos(4, 2, 2));
+	assert_eq!(index.pos(text.len()), pos(4, 3, 3));
+	assert_eq!(index.pos(100), pos(4, 3, 3));
+
+	Ok(())
+}
+
+#[test]
+fn test_line_index_pos_multi_byte_columns() -> Result<()> {
+	// -- Setup & Fixtures
+	// 'é' is 2 UTF-8 bytes and 1 UTF-16 unit, '😀' is 4 UTF-8 bytes and 2 UTF-16 units.
+	let text = "x\né😀<A>";
+	let index = LineIndex::new(text);
+	let tag_idx = text.find('<').ok_or("should have '<'")?;
+
+	// -- Exec & Check
+	assert_eq!(index.pos(tag_idx), pos(2, 7, 4));
+	// Inside '😀', resolved to its start.
+	assert_eq!(index.pos(tag_idx - 1), pos(2, 3, 2));
+
+	Ok(())
+}
@@ -0,0 +1,147 @@
+//! Owned attributes of an extracted element, in source order.
+
+use crate::tag::AttrsRef;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::borrow::Cow;
+
+/// The attributes of an opening tag as owned strings, in source order and with all duplicate keys.
+///
+/// The value is `None` for an attribute without `=` (a flag, e.g., `readonly`), and `Some("")` for
+/// an explicitly empty value (e.g., `readonly=""`).
+///
+/// Serializes as a map whose entries keep the source order, with `null` for flags.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Attrs {
+	entries: Vec<(String, Option<String>)>,
+}
+
+/// Constructors
+impl Attrs {
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+/// Accessors
+impl Attrs {
+	/// Returns the value of the last attribute with the key, like a map where the last value wins.
+	///
+	/// Returns `None` when the key is absent, or when that attribute is a flag without value.
+	pub fn get(&self, key: &str) -> Option<&str> {
+		self.last_entry(key).and_then(|(_, value)| value.as_deref())
+	}
+
+	/// Returns the values of all the attributes with the key, in source order, skipping flags.
+	pub fn get_all<'s>(&'s self, key: &'s str) -> impl Iterator<Item = &'s str> + 's {
+		self.entries
+			.iter()
+			.filter(move |(entry_key, _)| entry_key == key)
+			.filter_map(|(_, value)| value.as_deref())
+	}
+
+	pub fn contains_key(&self, key: &str) -> bool {
+		self.entries.iter().any(|(entry_key, _)| entry_key == key)
+	}
+
+	/// Returns whether the last attribute with the key is a flag without value (e.g., `readonly`).
+	pub fn is_flag(&self, key: &str) -> bool {
+		self.last_entry(key).is_some_and(|(_, value)| value.is_none())
+	}
+
+	/// Returns the key and the optional value of each attribute, in source order.
+	pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+		self.entries.iter().map(|(key, value)| (key.as_str(), value.as_deref()))
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Returns the optional value of the last attribute with the key, or `None` when the key is absent.
+	pub(crate) fn lookup(&self, key: &str) -> Option<Option<&str>> {
+		self.last_entry(key).map(|(_, value)| value.as_deref())
+	}
+
+	fn last_entry(&self, key: &str) -> Option<&(String, Option<String>)> {
+		self.entries.iter().rev().find(|(entry_key, _)| entry_key == key)
+	}
+}
+
+/// Setters
+impl Attrs {
+	/// Appends an attribute with a value, keeping any previous attribute with the same key.
+	pub fn push(&mut self, key: impl Into<String>, value: impl Into<String>) {
+		self.entries.push((key.into(), Some(value.into())));
+	}
+
+	/// Appends a flag attribute without value, keeping any previous attribute with the same key.
+	pub fn push_flag(&mut self, key: impl Into<String>) {
+		self.entries.push((key.into(), None));
+	}
+}
+
+// region:    --- Serialize
+
+impl Serialize for Attrs {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut map = serializer.serialize_map(Some(self.entries.len()))?;
+		for (key, value) in &self.entries {
+			map.serialize_entry(key, value)?;
+		}
+		map.end()
+	}
+}
+
+// endregion: --- Serialize
+
+// region:    --- Froms
+
+impl From<AttrsRef<'_>> for Attrs {
+	fn from(attrs: AttrsRef<'_>) -> Self {
+		let entries = attrs
+			.into_iter()
+			.map(|(key, value)| (key.to_string(), value.map(Cow::into_owned)))
+			.collect();
+
+		Attrs { entries }
+	}
+}
+
+/// Builds attributes that all have a value.
+impl<K: Into<String>, V: Into<String>, const N: usize> From<[(K, V); N]> for Attrs {
+	fn from(entries: [(K, V); N]) -> Self {
+		let entries = entries
+			.into_iter()
+			.map(|(key, value)| (key.into(), Some(value.into())))
+			.collect();
+
+		Attrs { entries }
+	}
+}
+
+/// Builds attributes from keys and optional values, where `None` is a flag.
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, Option<V>)> for Attrs {
+	fn from_iter<I: IntoIterator<Item = (K, Option<V>)>>(iter: I) -> Self {
+		let entries = iter
+			.into_iter()
+			.map(|(key, value)| (key.into(), value.map(Into::into)))
+			.collect();
+
+		Attrs { entries }
+	}
+}
+
+impl IntoIterator for Attrs {
+	type Item = (String, Option<String>);
+	type IntoIter = std::vec::IntoIter<Self::Item>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.entries.into_iter()
+	}
+}
+
+// endregion: --- Froms
@@ -0,0 +1,144 @@
+//! Borrowed attributes of an extracted element, in source order.
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::borrow::Cow;
+
+/// The attributes of an opening tag as references into the input, in source order and with all duplicate keys.
+///
+/// The value is `None` for an attribute without `=` (a flag, e.g., `readonly`), and `Some("")` for
+/// an explicitly empty value (e.g., `readonly=""`). Values are borrowed from the input, unless the
+/// configured `AttrDecoding` changes them.
+///
+/// Serializes as a map whose entries keep the source order, with `null` for flags.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AttrsRef<'a> {
+	entries: Vec<(&'a str, Option<Cow<'a, str>>)>,
+}
+
+/// Constructors
+impl AttrsRef<'_> {
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+/// Accessors
+impl<'a> AttrsRef<'a> {
+	/// Returns the value of the last attribute with the key, like a map where the last value wins.
+	///
+	/// Returns `None` when the key is absent, or when that attribute is a flag without value.
+	pub fn get(&self, key: &str) -> Option<&str> {
+		self.last_entry(key).and_then(|(_, value)| value.as_deref())
+	}
+
+	/// Returns the value of the last attribute with the key as stored, borrowed from the input
+	/// unless decoding changed it.
+	pub fn get_cow(&self, key: &str) -> Option<&Cow<'a, str>> {
+		self.last_entry(key).and_then(|(_, value)| value.as_ref())
+	}
+
+	/// Returns the values of all the attributes with the key, in source order, skipping flags.
+	pub fn get_all<'s>(&'s self, key: &'s str) -> impl Iterator<Item = &'s str> + 's {
+		self.entries
+			.iter()
+			.filter(move |(entry_key, _)| *entry_key == key)
+			.filter_map(|(_, value)| value.as_deref())
+	}
+
+	pub fn contains_key(&self, key: &str) -> bool {
+		self.entries.iter().any(|(entry_key, _)| *entry_key == key)
+	}
+
+	/// Returns whether the last attribute with the key is a flag without value (e.g., `readonly`).
+	pub fn is_flag(&self, key: &str) -> bool {
+		self.last_entry(key).is_some_and(|(_, value)| value.is_none())
+	}
+
+	/// Returns the key and the optional value of each attribute, in source order.
+	pub fn iter(&self) -> impl Iterator<Item = (&'a str, Option<&str>)> + '_ {
+		self.entries.iter().map(|(key, value)| (*key, value.as_deref()))
+	}
+
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Returns the optional value of the last attribute with the key, or `None` when the key is absent.
+	pub(crate) fn lookup(&self, key: &str) -> Option<Option<&str>> {
+		self.last_entry(key).map(|(_, value)| value.as_deref())
+	}
+
+	/// Returns the attributes as stored, in source order.
+	pub(crate) fn entries(&self) -> &[(&'a str, Option<Cow<'a, str>>)] {
+		&self.entries
+	}
+
+	fn last_entry(&self, key: &str) -> Option<&(&'a str, Option<Cow<'a, str>>)> {
+		self.entries.iter().rev().find(|(entry_key, _)| *entry_key == key)
+	}
+}
+
+/// Setters
+impl<'a> AttrsRef<'a> {
+	/// Appends an attribute with a value, keeping any previous attribute with the same key.
+	pub fn push(&mut self, key: &'a str, value: impl Into<Cow<'a, str>>) {
+		self.entries.push((key, Some(value.into())));
+	}
+
+	/// Appends a flag attribute without value, keeping any previous attribute with the same key.
+	pub fn push_flag(&mut self, key: &'a str) {
+		self.entries.push((key, None));
+	}
+}
+
+// region:    --- Serialize
+
+impl Serialize for AttrsRef<'_> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut map = serializer.serialize_map(Some(self.entries.len()))?;
+		for (key, value) in &self.entries {
+			map.serialize_entry(key, value)?;
+		}
+		map.end()
+	}
+}
+
+// endregion: --- Serialize
+
+// region:    --- Froms
+
+/// Builds attributes that all have a value.
+impl<'a, const N: usize> From<[(&'a str, &'a str); N]> for AttrsRef<'a> {
+	fn from(entries: [(&'a str, &'a str); N]) -> Self {
+		let entries = entries
+			.into_iter()
+			.map(|(key, value)| (key, Some(Cow::Borrowed(value))))
+			.collect();
+
+		AttrsRef { entries }
+	}
+}
+
+/// Builds attributes from keys and optional values, where `None` is a flag.
+impl<'a, V: Into<Cow<'a, str>>> FromIterator<(&'a str, Option<V>)> for AttrsRef<'a> {
+	fn from_iter<I: IntoIterator<Item = (&'a str, Option<V>)>>(iter: I) -> Self {
+		let entries = iter.into_iter().map(|(key, value)| (key, value.map(Into::into))).collect();
+
+		AttrsRef { entries }
+	}
+}
+
+impl<'a> IntoIterator for AttrsRef<'a> {
+	type Item = (&'a str, Option<Cow<'a, str>>);
+	type IntoIter = std::vec::IntoIter<Self::Item>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.entries.into_iter()
+	}
+}
+
+// endregion: --- Froms
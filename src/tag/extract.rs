@@ -1,6 +1,12 @@
 //! Parser module for extracting tag elements and text fragments from input.
 
-use crate::tag::{Parts, PartsRef, TagIter, TagOptions, TagRefIter};
+use crate::Result;
+use crate::tag::tag_tree_ref::build_nodes_ref;
+use crate::tag::{
+	Part, PartRef, Parts, PartsRef, TagDiagnostic, TagElemRef, TagIter, TagNameMatcher, TagOptions, TagRefIter,
+	TagTree, TagTreeRef,
+};
+use std::borrow::Cow;
 
 /// Parses the input string for the specified tag names.
 ///
@@ -30,6 +36,157 @@ pub fn extract_refs<'a>(input: &'a str, tag_names: &[&str], options: impl Into<T
 	PartsRef { parts }
 }
 
+/// Parses the input string for the specified tag names and also returns the structural problems found.
+///
+/// The parts are the same as those returned by `extract`. See [`TagDiagnostic`] for the reported problems.
+pub fn extract_with_diagnostics(
+	input: &str,
+	tag_names: &[&str],
+	options: impl Into<TagOptions>,
+) -> (Parts, Vec<TagDiagnostic>) {
+	let options = options.into();
+	let fence = options.fence_or_default();
+	let mut iter = TagRefIter::new(input, tag_names, options).with_diagnostics();
+	let parts = iter
+		.by_ref()
+		.map(|part_ref| Part::from_ref_in_fence(part_ref, &fence))
+		.collect();
+
+	(Parts { parts }, iter.into_diagnostics())
+}
+
+/// Parses the input string for the specified tag names, returning references and the structural problems found.
+pub fn extract_refs_with_diagnostics<'a>(
+	input: &'a str,
+	tag_names: &[&str],
+	options: impl Into<TagOptions>,
+) -> (PartsRef<'a>, Vec<TagDiagnostic>) {
+	let mut iter = TagRefIter::new(input, tag_names, options).with_diagnostics();
+	let parts = iter.by_ref().collect();
+
+	(PartsRef { parts }, iter.into_diagnostics())
+}
+
+/// Parses the input string for the specified tag names, failing on the first structural problem.
+///
+/// Unlike `extract`, a malformed opening tag, an unclosed element, or an unexpected closing tag
+/// returns the matching `Error` variant with its byte position instead of partial parts.
+/// Elements closed by auto-close are accepted, since auto-close is requested through the options.
+pub fn try_extract(input: &str, tag_names: &[&str], options: impl Into<TagOptions>) -> Result<Parts> {
+	let (parts, diagnostics) = extract_with_diagnostics(input, tag_names, options);
+	check_diagnostics(diagnostics)?;
+
+	Ok(parts)
+}
+
+/// Parses the input string for the specified tag names and returns references,
+/// failing on the first structural problem like `try_extract`.
+pub fn try_extract_refs<'a>(
+	input: &'a str,
+	tag_names: &[&str],
+	options: impl Into<TagOptions>,
+) -> Result<PartsRef<'a>> {
+	let (parts, diagnostics) = extract_refs_with_diagnostics(input, tag_names, options);
+	check_diagnostics(diagnostics)?;
+
+	Ok(parts)
+}
+
+/// Parses the input string for the tags whose names satisfy any of the matchers.
+///
+/// See [`TagRefIter::new_with_matchers`].
+pub fn extract_with_matchers(input: &str, matchers: &[TagNameMatcher], options: impl Into<TagOptions>) -> Parts {
+	let iter = TagIter::new_with_matchers(input, matchers, options);
+	let parts = iter.collect();
+
+	Parts { parts }
+}
+
+/// Parses the input string for the tags whose names satisfy any of the matchers and returns references.
+pub fn extract_refs_with_matchers<'a>(
+	input: &'a str,
+	matchers: &[TagNameMatcher],
+	options: impl Into<TagOptions>,
+) -> PartsRef<'a> {
+	let iter = TagRefIter::new_with_matchers(input, matchers, options);
+	let parts = iter.collect();
+
+	PartsRef { parts }
+}
+
+/// Parses the input string for the specified tag names and returns an element tree.
+///
+/// The content of each extracted element is parsed again with the same tag names and options,
+/// so nested elements become children. Text nodes are included only when text capture is enabled.
+/// Use [`TagOptions::with_nested`] when elements of the same name can contain each other.
+/// Elements deeper than 64 levels keep their content without child nodes.
+pub fn extract_tree(input: &str, tag_names: &[&str], options: impl Into<TagOptions>) -> TagTree {
+	let options = options.into();
+	let tree_ref = extract_tree_refs(input, tag_names, options);
+
+	TagTree::from_ref_in_fence(tree_ref, &options.fence_or_default())
+}
+
+/// Parses the input string for the specified tag names and returns an element tree of references.
+///
+/// Element `start_idx` and `end_idx` values are byte offsets into `input` at every depth.
+pub fn extract_tree_refs<'a>(input: &'a str, tag_names: &[&str], options: impl Into<TagOptions>) -> TagTreeRef<'a> {
+	let nodes = build_nodes_ref(input, tag_names, options.into());
+
+	TagTreeRef { nodes }
+}
+
+/// Replaces the elements of the specified tag names with the text returned by `replacer`,
+/// keeping the text around them intact, like `Regex::replace_all`.
+///
+/// `replacer` is called with each top-level element in source order. It returns the replacement
+/// for the whole element, from its opening tag through its closing tag, or `None` to keep the element as is.
+/// The input is borrowed when no element is replaced.
+pub fn replace_all<'a, F>(
+	input: &'a str,
+	tag_names: &[&str],
+	options: impl Into<TagOptions>,
+	mut replacer: F,
+) -> Cow<'a, str>
+where
+	F: FnMut(&TagElemRef<'a>) -> Option<Cow<'a, str>>,
+{
+	let options = options.into().with_capture_text(false);
+	let mut replaced = String::new();
+	let mut last_idx = 0;
+
+	for part in TagRefIter::new(input, tag_names, options) {
+		let PartRef::TagElemRef(elem) = part else {
+			continue;
+		};
+		if let Some(replacement) = replacer(&elem) {
+			replaced.push_str(&input[last_idx..elem.start_idx]);
+			replaced.push_str(&replacement);
+			last_idx = elem.end_idx + 1;
+		}
+	}
+
+	// Every replaced element ends past index 0
+	if last_idx == 0 {
+		return Cow::Borrowed(input);
+	}
+	replaced.push_str(&input[last_idx..]);
+
+	Cow::Owned(replaced)
+}
+
+// region:    --- Support
+
+/// Returns the error for the first diagnostic, in source order, that fails strict extraction.
+fn check_diagnostics(diagnostics: Vec<TagDiagnostic>) -> Result<()> {
+	match diagnostics.into_iter().find_map(TagDiagnostic::into_error) {
+		Some(err) => Err(err),
+		None => Ok(()),
+	}
+}
+
+// endregion: --- Support
+
 // region:    --- Tests
 
 #[path = "extract_tests.rs"]
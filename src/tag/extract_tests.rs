@@ -1,8 +1,16 @@
 //! Tests for the parser module.
 
-use super::extract;
-use crate::tag::{FENCE_BRACKETS, Part, TagElem, TagFence, TagOptions};
-use std::collections::HashMap;
+use super::{
+	extract, extract_refs, extract_refs_with_diagnostics, extract_tree, extract_tree_refs, extract_with_diagnostics,
+	replace_all, try_extract, try_extract_refs,
+};
+use crate::tag::tag_tree_ref::MAX_TREE_DEPTH;
+use crate::tag::{
+	AttrDecoding, Attrs, FENCE_BRACKETS, FENCE_XML, Part, RAW_SECTION_BRACKETS, RAW_SECTION_CDATA, TagDiagnostic,
+	TagDiagnosticKind, TagElem, TagFence, TagNode, TagNodeRef, TagOptions, TagSpan,
+};
+use crate::{Error, LineIndex, TextPos};
+use std::borrow::Cow;
 
 type Result<T> = core::result::Result<T, Box<dyn std::error::Error>>;
 
@@ -26,6 +34,7 @@ fn test_tag_parser_simple_with_text() -> Result<()> {
 			attrs: None,
 			content: "content".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(7, 27),
 		})
 	);
 	assert_eq!(result.parts()[2], Part::Text(" After".to_string()));
@@ -51,6 +60,7 @@ fn test_tag_parser_simple_without_text() -> Result<()> {
 			attrs: None,
 			content: "content".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(7, 27),
 		})
 	);
 
@@ -71,8 +81,7 @@ fn test_tag_parser_multiple_tags_with_attrs() -> Result<()> {
 
 	assert_eq!(result.parts()[0], Part::Text("Start ".to_string()));
 
-	let mut file_attrs = HashMap::new();
-	file_attrs.insert("path".to_string(), "a.txt".to_string());
+	let file_attrs = Attrs::from([("path", "a.txt")]);
 	assert_eq!(
 		result.parts()[1],
 		Part::TagElem(TagElem {
@@ -80,13 +89,13 @@ fn test_tag_parser_multiple_tags_with_attrs() -> Result<()> {
 			attrs: Some(file_attrs),
 			content: "file content".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(6, 44),
 		})
 	);
 
 	assert_eq!(result.parts()[2], Part::Text(" middle ".to_string()));
 
-	let mut data_attrs = HashMap::new();
-	data_attrs.insert("id".to_string(), "123".to_string());
+	let data_attrs = Attrs::from([("id", "123")]);
 	assert_eq!(
 		result.parts()[3],
 		Part::TagElem(TagElem {
@@ -94,6 +103,7 @@ fn test_tag_parser_multiple_tags_with_attrs() -> Result<()> {
 			attrs: Some(data_attrs),
 			content: "data content".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(52, 84),
 		})
 	);
 
@@ -157,6 +167,7 @@ fn test_tag_parser_only_tag() -> Result<()> {
 			attrs: None,
 			content: "content".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(0, 20),
 		})
 	);
 
@@ -181,6 +192,7 @@ fn test_tag_parser_adjacent_tags() -> Result<()> {
 			attrs: None,
 			content: "first".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(0, 12),
 		})
 	);
 	assert_eq!(
@@ -190,6 +202,7 @@ fn test_tag_parser_adjacent_tags() -> Result<()> {
 			attrs: None,
 			content: "second".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(12, 25),
 		})
 	);
 
@@ -310,8 +323,7 @@ fn test_tag_parser_bracket3_fence() -> Result<()> {
 	assert_eq!(result.parts().len(), 5);
 	assert_eq!(result.parts()[0], Part::Text("Before ".to_string()));
 
-	let mut file_attrs = HashMap::new();
-	file_attrs.insert("path".to_string(), "a.txt".to_string());
+	let file_attrs = Attrs::from([("path", "a.txt")]);
 	assert_eq!(
 		result.parts()[1],
 		Part::TagElem(TagElem {
@@ -319,13 +331,13 @@ fn test_tag_parser_bracket3_fence() -> Result<()> {
 			attrs: Some(file_attrs),
 			content: "file content".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(7, 53),
 		})
 	);
 
 	assert_eq!(result.parts()[2], Part::Text(" after ".to_string()));
 
-	let mut delete_attrs = HashMap::new();
-	delete_attrs.insert("path".to_string(), "temp.txt".to_string());
+	let delete_attrs = Attrs::from([("path", "temp.txt")]);
 	assert_eq!(
 		result.parts()[3],
 		Part::TagElem(TagElem {
@@ -333,6 +345,7 @@ fn test_tag_parser_bracket3_fence() -> Result<()> {
 			attrs: Some(delete_attrs),
 			content: "".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(60, 90),
 		})
 	);
 
@@ -344,14 +357,7 @@ fn test_tag_parser_bracket3_fence() -> Result<()> {
 #[test]
 fn test_tag_parser_custom_fence() -> Result<()> {
 	// -- Setup & Fixtures
-	let fence = TagFence {
-		name: "mustache",
-		open_delim: "{{",
-		close_delim: "}}",
-		close_delim_alts: None,
-		closing_tag_prefix: "/",
-		self_closing_suffix: "/",
-	};
+	let fence = TagFence::new("mustache", "{{", "}}");
 	let input = "{{DATA key=value}}payload{{/DATA}}";
 	let tag_names = ["DATA"];
 
@@ -359,8 +365,7 @@ fn test_tag_parser_custom_fence() -> Result<()> {
 	let result = extract(input, &tag_names, TagOptions::default().with_fence(fence));
 
 	// -- Check
-	let mut attrs = HashMap::new();
-	attrs.insert("key".to_string(), "value".to_string());
+	let attrs = Attrs::from([("key", "value")]);
 	assert_eq!(
 		result.parts(),
 		&[Part::TagElem(TagElem {
@@ -368,6 +373,7 @@ fn test_tag_parser_custom_fence() -> Result<()> {
 			attrs: Some(attrs),
 			content: "payload".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(0, 34),
 		})]
 	);
 
@@ -490,3 +496,500 @@ fn test_tag_extract_auto_close_with_options_and_normal_close_precedence() -> Res
 
 	Ok(())
 }
+
+#[test]
+fn test_tag_extract_tree_refs_absolute_offsets() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"Intro <PLAN><STEP id=1>do <FILE path="a.rs">fn a(){}</FILE></STEP><STEP id=2/></PLAN> end"#;
+	let tag_names = ["PLAN", "STEP", "FILE"];
+
+	// -- Exec
+	let tree = extract_tree_refs(input, &tag_names, TagOptions::default().with_capture_text(true));
+
+	// -- Check
+	let nodes = tree.nodes();
+	assert_eq!(nodes.len(), 3);
+	assert_eq!(nodes[0], TagNodeRef::Text("Intro "));
+	assert_eq!(nodes[2], TagNodeRef::Text(" end"));
+
+	let TagNodeRef::Elem(plan) = &nodes[1] else {
+		return Err("should extract a PLAN node".into());
+	};
+	assert_eq!(plan.elem.tag_name, "PLAN");
+	assert_eq!(
+		&input[plan.elem.start_idx..=plan.elem.end_idx],
+		&input[6..input.len() - 4]
+	);
+	assert_eq!(plan.children.len(), 2);
+
+	let TagNodeRef::Elem(step_1) = &plan.children[0] else {
+		return Err("should extract the first STEP node".into());
+	};
+	assert_eq!(step_1.children.len(), 2);
+	assert_eq!(step_1.children[0], TagNodeRef::Text("do "));
+
+	let TagNodeRef::Elem(file) = &step_1.children[1] else {
+		return Err("should extract the FILE node".into());
+	};
+	assert_eq!(file.elem.content, "fn a(){}");
+	assert_eq!(file.children, vec![TagNodeRef::Text("fn a(){}")]);
+	assert_eq!(
+		&input[file.elem.start_idx..=file.elem.end_idx],
+		r#"<FILE path="a.rs">fn a(){}</FILE>"#
+	);
+	let file_spans = &file.elem.spans;
+	assert_eq!(&input[file_spans.content.range()], "fn a(){}");
+	assert_eq!(
+		file_spans.attrs[0].value.map(|value| &input[value.range()]),
+		Some("a.rs")
+	);
+	assert_eq!(
+		file_spans.close_tag.map(|close_tag| &input[close_tag.range()]),
+		Some("</FILE>")
+	);
+
+	let TagNodeRef::Elem(step_2) = &plan.children[1] else {
+		return Err("should extract the self-closing STEP node".into());
+	};
+	assert_eq!(&input[step_2.elem.start_idx..=step_2.elem.end_idx], "<STEP id=2/>");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extract_tree_owned_nested_same_name() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<SECTION>a<SECTION>b</SECTION></SECTION>";
+
+	// -- Exec
+	let tree = extract_tree(input, &["SECTION"], TagOptions::default().with_nested(true));
+
+	// -- Check
+	let nodes = tree.into_nodes();
+	assert_eq!(nodes.len(), 1);
+	let TagNode::Elem(outer) = &nodes[0] else {
+		return Err("should extract the outer SECTION node".into());
+	};
+	assert_eq!(outer.elem.content, "a<SECTION>b</SECTION>");
+	assert_eq!(outer.children.len(), 1);
+	let TagNode::Elem(inner) = &outer.children[0] else {
+		return Err("should extract the inner SECTION node".into());
+	};
+	assert_eq!(inner.elem.content, "b");
+	assert!(inner.children.is_empty());
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extract_tree_deeply_nested() -> Result<()> {
+	// -- Setup & Fixtures
+	let depth = 4000;
+	let input = format!("{}x{}", "<S>".repeat(depth), "</S>".repeat(depth));
+	let options = TagOptions::default().with_nested(true);
+
+	// -- Exec
+	let tree_refs = extract_tree_refs(&input, &["S"], options);
+	let tree = extract_tree(&input, &["S"], options);
+
+	// -- Check
+	let mut nodes = tree_refs.nodes();
+	let mut levels = 0;
+	while let [TagNodeRef::Elem(node)] = nodes.as_slice() {
+		assert_eq!(node.elem.start_idx, levels * "<S>".len());
+		nodes = &node.children;
+		levels += 1;
+	}
+	// Elements past the maximum depth keep their content without child nodes.
+	assert_eq!(levels, MAX_TREE_DEPTH);
+	let [TagNodeRef::Elem(outer)] = tree_refs.nodes().as_slice() else {
+		return Err("should extract the outer S node".into());
+	};
+	assert_eq!(outer.elem.content.len(), input.len() - "<S></S>".len());
+	assert_eq!(tree.nodes().len(), 1);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extract_tree_refs_raw_sections_not_children() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<FILE><![CDATA[<FILE>x</FILE>]]></FILE><NOTE>a <![CDATA[b]]> <FILE>c</FILE></NOTE>";
+	let options = TagOptions::default().with_fence(FENCE_XML.with_raw_section(RAW_SECTION_CDATA));
+
+	// -- Exec
+	let tree = extract_tree_refs(input, &["FILE", "NOTE"], options);
+
+	// -- Check
+	let nodes = tree.nodes();
+	assert_eq!(nodes.len(), 2);
+	let TagNodeRef::Elem(file) = &nodes[0] else {
+		return Err("should extract the outer FILE node".into());
+	};
+	assert_eq!(file.elem.content, "<![CDATA[<FILE>x</FILE>]]>");
+	assert!(file.children.is_empty());
+
+	// Openings after a raw section are still children.
+	let TagNodeRef::Elem(note) = &nodes[1] else {
+		return Err("should extract the NOTE node".into());
+	};
+	assert_eq!(note.children.len(), 1);
+	let TagNodeRef::Elem(child) = &note.children[0] else {
+		return Err("should extract the FILE node after the raw section".into());
+	};
+	assert_eq!(child.elem.content, "c");
+	assert_eq!(&input[child.elem.spans.content.range()], "c");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extract_raw_sections_unwrapped_in_owned_content() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "[[[FILE]]]Use [[[!RAW]]][[[FILE]]]x[[[/FILE]]][[[/!RAW]]] as is.[[[/FILE]]]";
+	let options = TagOptions::default().with_fence(FENCE_BRACKETS.with_raw_section(RAW_SECTION_BRACKETS));
+
+	// -- Exec
+	let parts = extract(input, &["FILE"], options);
+	let tree = extract_tree(input, &["FILE"], options);
+	let parts_ref = extract_refs(input, &["FILE"], options);
+
+	// -- Check
+	let content = "Use [[[FILE]]]x[[[/FILE]]] as is.";
+	assert_eq!(parts.tag_elems().len(), 1);
+	assert_eq!(parts.tag_elems()[0].content, content);
+	let [TagNode::Elem(file)] = tree.nodes().as_slice() else {
+		return Err("should extract a single FILE node".into());
+	};
+	assert_eq!(file.elem.content, content);
+	assert!(file.children.is_empty());
+	// References keep the content as written.
+	assert_eq!(
+		parts_ref.tag_elems()[0].content,
+		"Use [[[!RAW]]][[[FILE]]]x[[[/FILE]]][[[/!RAW]]] as is."
+	);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extract_with_diagnostics_orphan_and_auto_closed() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "</DATA> <FILE>a</FILE> <FILE>b <DATA>c</DATA></FILE> end";
+	let options = TagOptions::default().with_auto_close(true);
+
+	// -- Exec
+	let (parts, diagnostics) = extract_with_diagnostics(input, &["FILE", "DATA"], options);
+
+	// -- Check
+	assert_eq!(parts, extract(input, &["FILE", "DATA"], options));
+	assert_eq!(
+		diagnostics,
+		[
+			TagDiagnostic::new(TagDiagnosticKind::OrphanClose, "DATA", 0, 6),
+			TagDiagnostic::new(TagDiagnosticKind::AutoClosed, "FILE", 23, 30),
+			TagDiagnostic::new(TagDiagnosticKind::OrphanClose, "FILE", 45, 51),
+		]
+	);
+	assert_eq!(&input[23..=30], "<FILE>b ");
+	assert_eq!(&input[45..=51], "</FILE>");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extract_with_diagnostics_malformed_and_unclosed() -> Result<()> {
+	// -- Setup & Fixtures
+	let malformed_input = "<DATA>ok</DATA> </DATA> <FILE path=\"a.rs\"";
+	let unclosed_input = "<FILE>ok</FILE> <FILE>never closed </DATA>";
+
+	// -- Exec
+	let (malformed_parts, malformed_diagnostics) =
+		extract_refs_with_diagnostics(malformed_input, &["FILE", "DATA"], TagOptions::default());
+	let (_, unclosed_diagnostics) = extract_refs_with_diagnostics(unclosed_input, &["FILE", "DATA"], None);
+
+	// -- Check
+	assert_eq!(malformed_parts.tag_elems().len(), 1);
+	assert_eq!(
+		malformed_diagnostics,
+		[
+			TagDiagnostic::new(TagDiagnosticKind::OrphanClose, "DATA", 16, 22),
+			TagDiagnostic::new(TagDiagnosticKind::MalformedOpen, "FILE", 24, 28),
+		]
+	);
+	// Extraction stops at the unclosed FILE, so the closing tags after it are not reported.
+	assert_eq!(
+		unclosed_diagnostics,
+		[TagDiagnostic::new(TagDiagnosticKind::Unclosed, "FILE", 16, 21)]
+	);
+	assert_eq!(
+		unclosed_diagnostics[0].to_string(),
+		"opening tag has no matching closing tag: `FILE` at bytes 16..=21"
+	);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_try_extract_ok_and_auto_closed() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<FILE>a</FILE> <FILE>b <DATA/> end";
+	let options = TagOptions::default().with_auto_close(true).with_capture_text(true);
+
+	// -- Exec
+	let parts = try_extract(input, &["FILE", "DATA"], options)?;
+	let parts_ref = try_extract_refs("<FILE>a</FILE>", &["FILE"], None)?;
+
+	// -- Check
+	assert_eq!(parts, extract(input, &["FILE", "DATA"], options));
+	assert_eq!(parts_ref.tag_elems().len(), 1);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_try_extract_first_structural_error() -> Result<()> {
+	// -- Setup & Fixtures
+	let tag_names = ["FILE", "DATA"];
+
+	// -- Exec
+	let unclosed = try_extract("<FILE>ok</FILE> <FILE>never closed", &tag_names, None);
+	let malformed = try_extract_refs("<DATA>ok</DATA> <FILE path=\"a.rs\"", &tag_names, None);
+	let unexpected = try_extract("<FILE>a</FILE> </DATA> <FILE>b", &tag_names, None);
+
+	// -- Check
+	assert!(matches!(
+		unclosed,
+		Err(Error::UnclosedTag { ref name, start_idx: 16 }) if name == "FILE"
+	));
+	assert!(matches!(
+		malformed,
+		Err(Error::MalformedOpenTag { ref name, start_idx: 16 }) if name == "FILE"
+	));
+	assert!(matches!(
+		unexpected,
+		Err(Error::UnexpectedClosingTag { ref name, start_idx: 15 }) if name == "DATA"
+	));
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extract_spans_resolve_to_line_col() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "intro\n  <FILE path=\"é.rs\">\nfn main() {}\n</FILE>\n<FILE/>";
+	let line_index = LineIndex::new(input);
+
+	// -- Exec
+	let parts_ref = extract_refs(input, &["FILE"], None);
+	let parts = extract(input, &["FILE"], None);
+
+	// -- Check
+	let elem_ref = &parts_ref.tag_elems()[0];
+	let span = elem_ref.span();
+	assert_eq!(span, TagSpan::new(elem_ref.start_idx, elem_ref.end_idx + 1));
+	assert!(input[span.range()].starts_with("<FILE path") && input[span.range()].ends_with("</FILE>"));
+	assert_eq!(
+		span.start_pos(&line_index),
+		TextPos {
+			line: 2,
+			col_utf8: 3,
+			col_utf16: 3
+		}
+	);
+	assert_eq!(
+		span.end_pos(&line_index),
+		TextPos {
+			line: 4,
+			col_utf8: 8,
+			col_utf16: 8
+		}
+	);
+	let spans: Vec<TagSpan> = parts.tag_elems().iter().map(|elem| elem.span).collect();
+	assert_eq!(spans, [span, TagSpan::new(input.len() - 7, input.len())]);
+	assert_eq!(spans[1].start_pos(&line_index).line, 5);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extract_span_ignored_by_eq_and_serialize() -> Result<()> {
+	// -- Setup & Fixtures
+	let first = extract("<FILE>a</FILE>", &["FILE"], None);
+	let second = extract("intro <FILE>a</FILE>", &["FILE"], None);
+
+	// -- Exec
+	let (first_elem, second_elem) = (&first.tag_elems()[0], &second.tag_elems()[0]);
+	let json = serde_json::to_value(second_elem)?;
+
+	// -- Check
+	assert_ne!(first_elem.span, second_elem.span);
+	assert_eq!(first_elem, second_elem);
+	assert_eq!(*first_elem, &TagElem::new("FILE", None, "a"));
+	assert!(json.get("span").is_none());
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extract_serialize_attrs_in_source_order() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"<FILE zeta="1" alpha="2" mid="3" alpha="4">x</FILE>"#;
+
+	// -- Exec
+	let parts = extract(input, &["FILE"], None);
+	let json = serde_json::to_string(&parts.tag_elems()[0].attrs)?;
+
+	// -- Check
+	assert_eq!(json, r#"{"zeta":"1","alpha":"2","mid":"3","alpha":"4"}"#);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extract_flag_and_empty_attrs() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"<FILE overwrite>a</FILE><FILE overwrite="">b</FILE>"#;
+
+	// -- Exec
+	let parts = extract(input, &["FILE"], None);
+	let parts_ref = extract_refs(input, &["FILE"], None);
+
+	// -- Check
+	let elems = parts.tag_elems();
+	let flag_attrs = elems[0].attrs.as_ref().ok_or("should have flag attrs")?;
+	let empty_attrs = elems[1].attrs.as_ref().ok_or("should have empty attrs")?;
+	assert!(flag_attrs.is_flag("overwrite") && flag_attrs.contains_key("overwrite"));
+	assert_eq!(flag_attrs.get("overwrite"), None);
+	assert!(!empty_attrs.is_flag("overwrite"));
+	assert_eq!(empty_attrs.get("overwrite"), Some(""));
+	assert_eq!(serde_json::to_string(flag_attrs)?, r#"{"overwrite":null}"#);
+	assert_eq!(serde_json::to_string(empty_attrs)?, r#"{"overwrite":""}"#);
+
+	let elem_refs = parts_ref.tag_elems();
+	let flag_attrs_ref = elem_refs[0].attrs.as_ref().ok_or("should have flag attrs")?;
+	assert_eq!(flag_attrs_ref.iter().collect::<Vec<_>>(), [("overwrite", None)]);
+	assert_eq!(
+		elem_refs[1].attrs.as_ref().map(|attrs| attrs.iter().collect::<Vec<_>>()),
+		Some(vec![("overwrite", Some(""))])
+	);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extract_with_attr_decoding() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"<COMMIT msg="fix \"parser\" bug" author=me>body</COMMIT>"#;
+	let options = TagOptions::default().with_attr_decoding(AttrDecoding::Backslash);
+
+	// -- Exec
+	let decoded = extract(input, &["COMMIT"], options);
+	let raw = extract(input, &["COMMIT"], None);
+
+	// -- Check
+	let decoded_attrs = decoded.tag_elems()[0].attrs.clone().ok_or("should have attrs")?;
+	assert_eq!(decoded_attrs.get("msg"), Some(r#"fix "parser" bug"#));
+	assert_eq!(decoded_attrs.get("author"), Some("me"));
+	let raw_attrs = raw.tag_elems()[0].attrs.clone().ok_or("should have attrs")?;
+	assert_eq!(raw_attrs.get("msg"), Some("fix \\"));
+	assert!(raw_attrs.contains_key(r#"parser\""#));
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extract_typed_attrs() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"<FILE path="a.rs" line="12" overwrite dry_run=No>body</FILE>"#;
+
+	// -- Exec
+	let parts = extract(input, &["FILE"], None);
+	let parts_ref = extract_refs(input, &["FILE"], None);
+
+	// -- Check
+	let elem = &parts.tag_elems()[0];
+	assert_eq!(elem.attr::<u32>("line")?, Some(12));
+	assert_eq!(elem.attr::<u32>("column")?, None);
+	assert_eq!(elem.required_attr::<String>("path")?, "a.rs");
+	assert_eq!(elem.attr_or::<u32>("column", 1)?, 1);
+	assert_eq!(elem.attr_bool("overwrite")?, Some(true));
+	assert_eq!(elem.attr_bool("dry_run")?, Some(false));
+	assert_eq!(elem.attr_bool("force")?, None);
+
+	let elem_ref = &parts_ref.tag_elems()[0];
+	assert_eq!(elem_ref.attr::<u32>("line")?, Some(12));
+	assert_eq!(elem_ref.attr_bool("overwrite")?, Some(true));
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extract_typed_attrs_errors() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"<FILE line="twelve" overwrite=maybe readonly>body</FILE>"#;
+
+	// -- Exec
+	let parts_ref = extract_refs(input, &["FILE"], None);
+
+	// -- Check
+	let elem_ref = &parts_ref.tag_elems()[0];
+	assert!(matches!(
+		elem_ref.attr::<u32>("line"),
+		Err(Error::InvalidAttr { tag_name, key, value, .. }) if tag_name == "FILE" && key == "line" && value == "twelve"
+	));
+	assert!(matches!(
+		elem_ref.required_attr::<String>("path"),
+		Err(Error::MissingAttr { tag_name, key }) if tag_name == "FILE" && key == "path"
+	));
+	assert!(matches!(
+		elem_ref.attr_bool("overwrite"),
+		Err(Error::InvalidAttr { .. })
+	));
+	// A flag has no value to convert, except as a bool.
+	assert!(matches!(
+		elem_ref.attr::<String>("readonly"),
+		Err(Error::InvalidAttr { .. })
+	));
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_replace_all_keeps_surrounding_text() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"Intro <SECRET>abc</SECRET> then <INCLUDE path="a.md"/> and <INCLUDE path="skip.md"/> end"#;
+
+	// -- Exec
+	let replaced = replace_all(input, &["SECRET", "INCLUDE"], None, |elem| match elem.tag_name {
+		"SECRET" => Some(Cow::Borrowed("***")),
+		_ => match elem.attrs.as_ref().and_then(|attrs| attrs.get("path")) {
+			Some("a.md") => Some(Cow::Owned(format!("[body of {}]", elem.tag_name))),
+			_ => None,
+		},
+	});
+
+	// -- Check
+	assert_eq!(
+		replaced,
+		r#"Intro *** then [body of INCLUDE] and <INCLUDE path="skip.md"/> end"#
+	);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_replace_all_borrows_when_unchanged() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "a <B>b</B> c <B>d</B>";
+
+	// -- Exec
+	let unchanged = replace_all(input, &["B"], None, |_| None);
+	let unwrapped = replace_all(input, &["B"], None, |elem| Some(Cow::Borrowed(elem.content)));
+
+	// -- Check
+	assert!(matches!(unchanged, Cow::Borrowed(text) if text == input));
+	assert_eq!(unwrapped, "a b c d");
+
+	Ok(())
+}
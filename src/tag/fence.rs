@@ -1,6 +1,8 @@
 //! Defines delimiter configurations for tag extraction.
 #![doc = include_str!("../../docs/rustdoc/tag/fence.md")]
 
+use std::borrow::Cow;
+
 /// A delimiter configuration used to parse tagged elements.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct TagFence {
@@ -21,6 +23,107 @@ pub struct TagFence {
 
 	/// The suffix between tag attributes and the closing delimiter of a self-closing tag.
 	pub self_closing_suffix: &'static str,
+
+	/// The optional delimiters of raw sections in element content, whose text never ends the element.
+	pub raw_section: Option<RawSection>,
+}
+
+/// Constructors
+impl TagFence {
+	/// Creates a fence with the `/` closing tag prefix and self-closing suffix, and no fallback delimiters
+	/// or raw sections.
+	///
+	/// Prefer it to a struct literal, so the fence keeps compiling as optional fields are added.
+	pub const fn new(name: &'static str, open_delim: &'static str, close_delim: &'static str) -> Self {
+		Self {
+			name,
+			open_delim,
+			close_delim,
+			close_delim_alts: None,
+			closing_tag_prefix: "/",
+			self_closing_suffix: "/",
+			raw_section: None,
+		}
+	}
+}
+
+/// Chainable Setters
+impl TagFence {
+	/// Sets the fallback delimiters accepted in addition to `close_delim`.
+	pub const fn with_close_delim_alts(mut self, close_delim_alts: &'static [&'static str]) -> Self {
+		self.close_delim_alts = Some(close_delim_alts);
+		self
+	}
+
+	/// Sets the prefix between the opening delimiter and a closing tag name.
+	pub const fn with_closing_tag_prefix(mut self, closing_tag_prefix: &'static str) -> Self {
+		self.closing_tag_prefix = closing_tag_prefix;
+		self
+	}
+
+	/// Sets the suffix between tag attributes and the closing delimiter of a self-closing tag.
+	pub const fn with_self_closing_suffix(mut self, self_closing_suffix: &'static str) -> Self {
+		self.self_closing_suffix = self_closing_suffix;
+		self
+	}
+
+	/// Sets the delimiters of raw sections in element content (e.g., `FENCE_XML.with_raw_section(RAW_SECTION_CDATA)`).
+	pub const fn with_raw_section(mut self, raw_section: RawSection) -> Self {
+		self.raw_section = Some(raw_section);
+		self
+	}
+}
+
+/// Content support
+impl TagFence {
+	/// Returns `content` without the delimiters of its raw sections, borrowed when the fence has no raw sections
+	/// or the content contains none.
+	pub fn unwrap_raw_sections<'a>(&self, content: &'a str) -> Cow<'a, str> {
+		match self.raw_section {
+			Some(raw_section) => raw_section.unwrap_sections(content),
+			None => Cow::Borrowed(content),
+		}
+	}
+}
+
+/// The delimiters of a raw section, like `<![CDATA[ ... ]]>`, enabled with `TagFence::with_raw_section`.
+///
+/// Inside the content of an element, the text of a raw section is skipped when searching for the closing tag,
+/// and the delimiters are removed from the owned content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawSection {
+	/// The delimiter that starts a raw section.
+	pub open: &'static str,
+
+	/// The delimiter that ends a raw section.
+	pub close: &'static str,
+}
+
+/// Content support
+impl RawSection {
+	/// Returns `content` without the delimiters of its raw sections, borrowed when it has none.
+	///
+	/// An open delimiter without a close delimiter after it is kept, since it does not start a raw section.
+	pub fn unwrap_sections<'a>(&self, content: &'a str) -> Cow<'a, str> {
+		let mut unwrapped = String::new();
+		let mut rest = content;
+		while let Some(open_idx) = rest.find(self.open) {
+			let body = &rest[open_idx + self.open.len()..];
+			let Some(close_idx) = body.find(self.close) else {
+				break;
+			};
+			unwrapped.push_str(&rest[..open_idx]);
+			unwrapped.push_str(&body[..close_idx]);
+			rest = &body[close_idx + self.close.len()..];
+		}
+
+		if rest.len() == content.len() {
+			return Cow::Borrowed(content);
+		}
+		unwrapped.push_str(rest);
+
+		Cow::Owned(unwrapped)
+	}
 }
 
 /// The XML-compatible fence used by the existing extraction APIs.
@@ -31,6 +134,7 @@ pub const FENCE_XML: TagFence = TagFence {
 	close_delim_alts: None,
 	closing_tag_prefix: "/",
 	self_closing_suffix: "/",
+	raw_section: None,
 };
 
 /// A triple-square-bracket fence for clearly separating structured payloads.
@@ -41,4 +145,19 @@ pub const FENCE_BRACKETS: TagFence = TagFence {
 	close_delim_alts: Some(&["]]"]),
 	closing_tag_prefix: "/",
 	self_closing_suffix: "/",
+	raw_section: None,
+};
+
+/// The XML CDATA raw section, `<![CDATA[ ... ]]>`, for `FENCE_XML`.
+pub const RAW_SECTION_CDATA: RawSection = RawSection {
+	open: "<![CDATA[",
+	close: "]]>",
+};
+
+/// The bracket raw section, `[[[!RAW]]] ... [[[/!RAW]]]`, for `FENCE_BRACKETS`.
+///
+/// The `!` keeps its delimiters from being read as tags, even when extracting any tag name.
+pub const RAW_SECTION_BRACKETS: RawSection = RawSection {
+	open: "[[[!RAW]]]",
+	close: "[[[/!RAW]]]",
 };
@@ -4,27 +4,58 @@
 
 mod support;
 
+mod attrs;
+mod attrs_ref;
 mod extract;
 mod fence;
 mod options;
+mod partial_tag_elem;
 mod parts;
 mod parts_ref;
 #[allow(clippy::module_inception)]
 mod tag;
+mod tag_deserializer;
+mod tag_diagnostic;
+mod tag_extractor;
 mod tag_iter;
+mod tag_name_matcher;
 mod tag_ref;
 mod tag_ref_iter;
+mod tag_span;
+mod tag_stream_parser;
+mod tag_tree;
+mod tag_tree_ref;
 
+pub use attrs::*;
+pub use attrs_ref::*;
 pub use extract::extract;
 pub use extract::extract_refs;
+pub use extract::extract_refs_with_diagnostics;
+pub use extract::extract_refs_with_matchers;
+pub use extract::extract_tree;
+pub use extract::extract_tree_refs;
+pub use extract::extract_with_diagnostics;
+pub use extract::extract_with_matchers;
+pub use extract::replace_all;
+pub use extract::try_extract;
+pub use extract::try_extract_refs;
 pub use fence::*;
-pub use options::TagOptions;
+pub use options::{AttrDecoding, MalformedPolicy, TagOptions};
+pub use partial_tag_elem::*;
 pub use parts::Parts;
 pub use parts::*;
 pub use parts_ref::*;
 pub use tag::*;
+pub use tag_deserializer::CONTENT_KEY;
+pub use tag_diagnostic::*;
+pub use tag_extractor::*;
 pub use tag_iter::*;
+pub use tag_name_matcher::*;
 pub use tag_ref::*;
 pub use tag_ref_iter::*;
+pub use tag_span::*;
+pub use tag_stream_parser::*;
+pub use tag_tree::*;
+pub use tag_tree_ref::*;
 
 // endregion: --- Modules
@@ -13,6 +13,52 @@ pub struct TagOptions {
 
 	/// Whether to include text fragments outside extracted tags.
 	pub capture_text: bool,
+
+	/// Whether same-name openings nest, so closing tags are matched by depth.
+	pub nested: bool,
+
+	/// Whether to extract tags with any well-formed name, in addition to the configured names.
+	pub wildcard: bool,
+
+	/// Whether opening and closing tag names match regardless of ASCII case.
+	pub case_insensitive_names: bool,
+
+	/// How extraction handles a malformed or unclosed opening tag.
+	pub malformed_policy: MalformedPolicy,
+
+	/// How attribute values are decoded.
+	pub attr_decoding: AttrDecoding,
+
+	/// The optional attribute declaring a heredoc-style terminator, so the element ends at the first line
+	/// consisting of its value instead of at a closing tag (e.g., `end` for `<FILE end="@@END@@">`).
+	pub terminator_attr: Option<&'static str>,
+}
+
+/// How extraction handles an opening tag without a close delimiter or without a matching closing tag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MalformedPolicy {
+	/// Stop extracting at the opening tag. With text capture, the rest of the input is returned as text.
+	#[default]
+	Stop,
+
+	/// Treat the opening tag as text and continue extracting the elements after it.
+	Recover,
+}
+
+/// How attribute values are decoded, and how the end of a quoted value is found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AttrDecoding {
+	/// Values are returned as written, and a quoted value ends at the next matching quote.
+	#[default]
+	Raw,
+
+	/// Backslash escapes (`\"`, `\'`, `\\`, `\n`, `\r`, `\t`, `\u{3e}`) are decoded, and an escaped quote
+	/// does not end a quoted value (e.g., `title="say \"hi\""`).
+	Backslash,
+
+	/// XML/HTML entities (`&quot;`, `&apos;`, `&amp;`, `&lt;`, `&gt;`) and numeric character
+	/// references (`&#34;`, `&#x22;`) are decoded.
+	Entities,
 }
 
 /// Chainable Setters
@@ -34,6 +80,42 @@ impl TagOptions {
 		self.auto_close = auto_close;
 		self
 	}
+
+	/// Sets whether same-name elements nest, matching closing tags by depth.
+	pub fn with_nested(mut self, nested: bool) -> Self {
+		self.nested = nested;
+		self
+	}
+
+	/// Sets whether extraction accepts any well-formed tag name (wildcard mode).
+	pub fn with_wildcard(mut self, wildcard: bool) -> Self {
+		self.wildcard = wildcard;
+		self
+	}
+
+	/// Sets whether tag names match regardless of ASCII case (e.g., `<file>` for `FILE`).
+	pub fn with_case_insensitive_names(mut self, case_insensitive_names: bool) -> Self {
+		self.case_insensitive_names = case_insensitive_names;
+		self
+	}
+
+	/// Sets how extraction handles a malformed or unclosed opening tag.
+	pub fn with_malformed_policy(mut self, malformed_policy: MalformedPolicy) -> Self {
+		self.malformed_policy = malformed_policy;
+		self
+	}
+
+	/// Sets how attribute values are decoded.
+	pub fn with_attr_decoding(mut self, attr_decoding: AttrDecoding) -> Self {
+		self.attr_decoding = attr_decoding;
+		self
+	}
+
+	/// Sets the attribute whose value, when present, is the line that ends the element instead of a closing tag.
+	pub fn with_terminator_attr(mut self, terminator_attr: &'static str) -> Self {
+		self.terminator_attr = Some(terminator_attr);
+		self
+	}
 }
 
 /// Accessors
@@ -77,6 +159,12 @@ mod tests {
 		assert!(!options.capture_text);
 		assert_eq!(options.fence, None);
 		assert!(!options.auto_close);
+		assert!(!options.nested);
+		assert!(!options.wildcard);
+		assert!(!options.case_insensitive_names);
+		assert_eq!(options.malformed_policy, MalformedPolicy::Stop);
+		assert_eq!(options.attr_decoding, AttrDecoding::Raw);
+		assert_eq!(options.terminator_attr, None);
 
 		Ok(())
 	}
@@ -89,12 +177,24 @@ mod tests {
 		let options = TagOptions::default()
 			.with_capture_text(true)
 			.with_fence(FENCE_BRACKETS)
-			.with_auto_close(true);
+			.with_auto_close(true)
+			.with_nested(true)
+			.with_wildcard(true)
+			.with_case_insensitive_names(true)
+			.with_malformed_policy(MalformedPolicy::Recover)
+			.with_attr_decoding(AttrDecoding::Entities)
+			.with_terminator_attr("end");
 
 		// -- Check
 		assert!(options.capture_text);
 		assert_eq!(options.fence, Some(FENCE_BRACKETS));
 		assert!(options.auto_close);
+		assert!(options.nested);
+		assert!(options.wildcard);
+		assert!(options.case_insensitive_names);
+		assert_eq!(options.malformed_policy, MalformedPolicy::Recover);
+		assert_eq!(options.attr_decoding, AttrDecoding::Entities);
+		assert_eq!(options.terminator_attr, Some("end"));
 
 		Ok(())
 	}
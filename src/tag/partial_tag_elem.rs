@@ -0,0 +1,40 @@
+//! Snapshot of a tag element that may still be receiving content.
+
+use crate::tag::{Attrs, TagElem};
+use serde::Serialize;
+use std::borrow::Cow;
+
+// region:    --- PartialTagElem
+
+/// A snapshot of a tag element whose content may still be streaming, like `<FILE path="a.rs">fn ma`.
+///
+/// The content of a snapshot returned by `TagStreamParser::partial_elem` borrows from the parser buffer.
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+pub struct PartialTagElem<'a> {
+	pub tag: String,
+
+	pub attrs: Option<Attrs>,
+
+	/// The content received so far, excluding any trailing bytes that may start the closing tag.
+	pub content: Cow<'a, str>,
+
+	/// Whether the element is complete, so `content` is final.
+	pub is_complete: bool,
+}
+
+// endregion: --- PartialTagElem
+
+// region:    --- Froms
+
+impl From<TagElem> for PartialTagElem<'static> {
+	fn from(elem: TagElem) -> Self {
+		PartialTagElem {
+			tag: elem.tag,
+			attrs: elem.attrs,
+			content: Cow::Owned(elem.content),
+			is_complete: true,
+		}
+	}
+}
+
+// endregion: --- Froms
@@ -1,6 +1,9 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use crate::tag::TagElem;
+use crate::tag::extract;
+use crate::tag::support::{find_closing_collision, find_round_trip_mismatch, write_elem_markup};
+use crate::tag::{TagElem, TagFence, TagOptions};
+use crate::{Error, Result};
 
 /// Represents a part of parsed content, either plain text or a tag element.
 #[derive(Debug, Serialize, Clone, PartialEq)]
@@ -21,12 +24,31 @@ impl<'a> From<crate::tag::PartRef<'a>> for Part {
 	}
 }
 
+/// Conversions
+impl Part {
+	/// Converts `part_ref` like `From`, removing the delimiters of the raw sections of `fence` from element content.
+	pub(crate) fn from_ref_in_fence(part_ref: crate::tag::PartRef<'_>, fence: &TagFence) -> Self {
+		match part_ref {
+			crate::tag::PartRef::Text(text) => Part::Text(text.to_string()),
+			crate::tag::PartRef::TagElemRef(tag_ref) => Part::TagElem(TagElem::from_ref_in_fence(tag_ref, fence)),
+		}
+	}
+}
+
 /// Result of extracting data and parts from input.
 #[derive(Debug, Serialize, Clone, PartialEq, Default)]
 pub struct Parts {
 	pub(crate) parts: Vec<Part>,
 }
 
+/// Constructors
+impl Parts {
+	/// Creates `Parts` from parts built by hand or returned by a `TagStreamParser`, for example to render them.
+	pub fn new(parts: Vec<Part>) -> Self {
+		Parts { parts }
+	}
+}
+
 impl Parts {
 	pub fn parts(&self) -> &Vec<Part> {
 		&self.parts
@@ -97,6 +119,78 @@ impl Parts {
 			.collect()
 	}
 
+	/// Returns the parts as text under the fence of the options, with each text part as is
+	/// and each element as markup (see [`TagElem::to_markup`]).
+	///
+	/// Attribute values are escaped for the `attr_decoding` of the options, so extracting
+	/// the rendered text with the same options returns the same values. With `AttrDecoding::Raw`,
+	/// fails with `Error::InvalidAttr` for a value that cannot be written without escapes.
+	pub fn render(&self, options: &TagOptions) -> Result<String> {
+		let fence = options.fence_or_default();
+		let mut rendered = String::new();
+		for part in &self.parts {
+			match part {
+				Part::Text(text) => rendered.push_str(text),
+				Part::TagElem(elem) => write_elem_markup(&mut rendered, elem, &fence, options.attr_decoding)?,
+			}
+		}
+		Ok(rendered)
+	}
+
+	/// Returns the parts rendered under the first of the `fences` for which extracting the rendered text
+	/// returns the same parts, with that fence.
+	///
+	/// The other settings come from `options`. A fence is skipped when the content of an element contains
+	/// its closing tag under it, when an attribute value cannot be written under it (see `render`), or when
+	/// extracting the rendered text with the fence, the tag names of the parts, and text capture does not
+	/// return the same parts. Elements are compared by tag name, attributes, and content, and adjacent text
+	/// parts are merged, so `extract(render(parts)) == parts` up to spans, auto-closing, and text splits
+	/// (e.g., the parts of a `TagStreamParser`, wrapped with `Parts::new`).
+	///
+	/// Fails with `Error::RenderCollision`, naming the first mismatching element under the last fence,
+	/// when no fence qualifies.
+	pub fn render_checked(&self, options: &TagOptions, fences: &[TagFence]) -> Result<(String, TagFence)> {
+		let tag_names = self.tag_names();
+		let mut tag_name = String::new();
+
+		for fence in fences {
+			let options = options.with_fence(*fence);
+			if let Some(elem) = find_closing_collision(self.tag_elems(), fence) {
+				tag_name = elem.tag.clone();
+				continue;
+			}
+
+			let rendered = match self.render(&options) {
+				Ok(rendered) => rendered,
+				Err(Error::InvalidAttr {
+					tag_name: attr_tag_name,
+					..
+				}) => {
+					tag_name = attr_tag_name;
+					continue;
+				}
+				Err(err) => return Err(err),
+			};
+			let extracted = extract(&rendered, &tag_names, options.with_capture_text(true));
+			match find_round_trip_mismatch(&self.parts, &extracted.parts) {
+				Some(mismatch_tag_name) => tag_name = mismatch_tag_name,
+				None => return Ok((rendered, *fence)),
+			}
+		}
+
+		Err(Error::RenderCollision {
+			tag_name,
+			fences: fences.iter().map(|fence| fence.name.to_string()).collect(),
+		})
+	}
+
+	/// Deserializes each `TagElem` into a serde type, typically an enum with a variant per tag name.
+	///
+	/// See [`TagElem::deserialize_as`].
+	pub fn deserialize_elems<'de, T: Deserialize<'de>>(&'de self) -> Result<Vec<T>> {
+		self.tag_elems().into_iter().map(TagElem::deserialize_as).collect()
+	}
+
 	/// Consumes the parsed data and returns the tag elements along with all text concatenated into a single string.
 	pub fn into_with_extrude_content(self) -> (Vec<TagElem>, String) {
 		let mut tag_elems = Vec::new();
@@ -133,6 +227,18 @@ impl<'a> IntoIterator for &'a Parts {
 
 // region:    --- Froms
 
+impl From<Vec<Part>> for Parts {
+	fn from(parts: Vec<Part>) -> Self {
+		Parts::new(parts)
+	}
+}
+
+impl FromIterator<Part> for Parts {
+	fn from_iter<I: IntoIterator<Item = Part>>(iter: I) -> Self {
+		Parts::new(iter.into_iter().collect())
+	}
+}
+
 impl From<Parts> for Vec<Part> {
 	fn from(val: Parts) -> Self {
 		val.parts
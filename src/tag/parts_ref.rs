@@ -1,4 +1,6 @@
+use crate::Result;
 use crate::tag::{PartRef, TagElemRef};
+use serde::Deserialize;
 
 /// Result of extracting data and parts from input as references.
 #[derive(Debug, PartialEq, Default)]
@@ -53,6 +55,13 @@ impl<'a> PartsRef<'a> {
 			})
 			.collect()
 	}
+
+	/// Deserializes each `TagElemRef` into a serde type, typically an enum with a variant per tag name.
+	///
+	/// See [`TagElemRef::deserialize_as`].
+	pub fn deserialize_elems<T: Deserialize<'a>>(&self) -> Result<Vec<T>> {
+		self.tag_elems().into_iter().map(TagElemRef::deserialize_as).collect()
+	}
 }
 
 impl<'a> IntoIterator for PartsRef<'a> {
@@ -1,58 +1,216 @@
-use std::collections::HashMap;
+use crate::tag::{AttrDecoding, AttrsRef, TagAttrSpan, TagSpan};
+use std::borrow::Cow;
+use std::ops::Range;
 
-/// Parses a raw string of attributes (key=value pairs) into a HashMap of references.
-pub fn parse_attrs_ref(attrs_raw: Option<&str>) -> Option<HashMap<&str, &str>> {
-	let raw = attrs_raw?.trim();
-	if raw.is_empty() {
-		return None;
-	}
+/// Parses a raw string of attributes (key=value pairs) into references, in source order.
+///
+/// Values are borrowed from `attrs_raw`, unless `decoding` changes them.
+pub fn parse_attrs_ref(attrs_raw: Option<&str>, decoding: AttrDecoding) -> Option<AttrsRef<'_>> {
+	let raw = attrs_raw?;
+	let attrs: AttrsRef = attr_ranges(raw, decoding)
+		.map(|(key, value)| (&raw[key], value.map(|value| decode_value(&raw[value], decoding))))
+		.collect();
+
+	if attrs.is_empty() { None } else { Some(attrs) }
+}
 
-	let mut attrs = HashMap::new();
-	let mut current = raw;
+/// Returns the spans of each attribute key and value of a raw string of attributes in source order,
+/// where `offset` is the byte index of the raw string in the input.
+///
+/// A value span covers the value as written in the input, before decoding.
+pub fn parse_attr_spans(attrs_raw: &str, offset: usize, decoding: AttrDecoding) -> Vec<TagAttrSpan> {
+	let shifted = |range: Range<usize>| TagSpan::new(range.start + offset, range.end + offset);
 
-	while !current.is_empty() {
-		current = current.trim_start();
-		if current.is_empty() {
-			break;
-		}
+	attr_ranges(attrs_raw, decoding)
+		.map(|(key, value)| TagAttrSpan {
+			key: shifted(key),
+			value: value.map(shifted),
+		})
+		.collect()
+}
+
+// region:    --- Support
+
+/// Returns the byte ranges of each attribute key and value (excluding quotes) in `raw`, in source order.
+///
+/// The value range is `None` when the key is not followed by `=`. With backslash decoding,
+/// an escaped quote does not end a quoted value.
+fn attr_ranges(raw: &str, decoding: AttrDecoding) -> impl Iterator<Item = (Range<usize>, Option<Range<usize>>)> + '_ {
+	let raw = raw.trim_end();
+	let mut pos = 0;
+
+	std::iter::from_fn(move || {
+		loop {
+			pos = skip_whitespace(raw, pos);
+			if pos == raw.len() {
+				return None;
+			}
+
+			// Find key
+			let rest = &raw[pos..];
+			let key_len = rest.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(rest.len());
+			let key = pos..pos + key_len;
+			pos = skip_whitespace(raw, key.end);
 
-		// Find key
-		let key_end = current.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(current.len());
-		let key = &current[..key_end];
-		current = current[key_end..].trim_start();
-
-		let mut value = "";
-		if current.starts_with('=') {
-			current = current[1..].trim_start();
-			if !current.is_empty() {
-				let first_char = current.chars().next().unwrap();
-				if first_char == '"' || first_char == '\'' {
-					let quote = first_char;
-					current = &current[1..];
-					if let Some(val_end) = current.find(quote) {
-						value = &current[..val_end];
-						current = &current[val_end + 1..];
-					} else {
-						// Unclosed quote, take rest as value
-						value = current;
-						current = "";
+			let mut value = None;
+			if raw[pos..].starts_with('=') {
+				pos = skip_whitespace(raw, pos + 1);
+				let rest = &raw[pos..];
+				let value_range = match rest.chars().next() {
+					None => pos..pos,
+					Some(quote @ ('"' | '\'')) => {
+						let value_start = pos + 1;
+						match find_closing_quote(&raw[value_start..], quote, decoding) {
+							Some(value_len) => {
+								pos = value_start + value_len + 1;
+								value_start..value_start + value_len
+							}
+							// Unclosed quote, take rest as value
+							None => {
+								pos = raw.len();
+								value_start..raw.len()
+							}
+						}
 					}
-				} else {
-					let val_end = current.find(|c: char| c.is_whitespace()).unwrap_or(current.len());
-					value = &current[..val_end];
-					current = &current[val_end..];
+					Some(_) => {
+						let value_len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+						pos += value_len;
+						pos - value_len..pos
+					}
+				};
+				value = Some(value_range);
+			}
+
+			if !key.is_empty() {
+				return Some((key, value));
+			}
+		}
+	})
+}
+
+fn skip_whitespace(raw: &str, pos: usize) -> usize {
+	raw.len() - raw[pos..].trim_start().len()
+}
+
+/// Returns the byte index of the quote that ends a quoted value, skipping escaped quotes with backslash decoding.
+fn find_closing_quote(value: &str, quote: char, decoding: AttrDecoding) -> Option<usize> {
+	if decoding != AttrDecoding::Backslash {
+		return value.find(quote);
+	}
+
+	let mut chars = value.char_indices();
+	while let Some((idx, c)) = chars.next() {
+		match c {
+			'\\' => {
+				chars.next();
+			}
+			c if c == quote => return Some(idx),
+			_ => {}
+		}
+	}
+	None
+}
+
+/// Decodes a value as written in the input, borrowing it when decoding does not change it.
+fn decode_value(value: &str, decoding: AttrDecoding) -> Cow<'_, str> {
+	match decoding {
+		AttrDecoding::Raw => Cow::Borrowed(value),
+		AttrDecoding::Backslash if value.contains('\\') => Cow::Owned(decode_backslash_escapes(value)),
+		AttrDecoding::Entities if value.contains('&') => Cow::Owned(decode_entities(value)),
+		AttrDecoding::Backslash | AttrDecoding::Entities => Cow::Borrowed(value),
+	}
+}
+
+/// Decodes `\\`, `\"`, `\'`, `\n`, `\r`, `\t`, and `\u{HEX}`. Other backslashes are kept (e.g., `C:\dir`).
+fn decode_backslash_escapes(value: &str) -> String {
+	let mut decoded = String::with_capacity(value.len());
+	let mut chars = value.chars();
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			decoded.push(c);
+			continue;
+		}
+		match chars.next() {
+			Some(escaped @ ('\\' | '"' | '\'')) => decoded.push(escaped),
+			Some('n') => decoded.push('\n'),
+			Some('r') => decoded.push('\r'),
+			Some('t') => decoded.push('\t'),
+			Some('u') => match decode_unicode_escape(chars.as_str()) {
+				Some((c, len)) => {
+					decoded.push(c);
+					chars = chars.as_str()[len..].chars();
 				}
+				None => decoded.push_str("\\u"),
+			},
+			Some(other) => {
+				decoded.push('\\');
+				decoded.push(other);
 			}
+			None => decoded.push('\\'),
 		}
+	}
+	decoded
+}
 
-		if !key.is_empty() {
-			attrs.insert(key, value);
+/// Returns the character of a `{HEX}` escape at the start of `rest`, after `\u`, and the length of the escape.
+fn decode_unicode_escape(rest: &str) -> Option<(char, usize)> {
+	let hex_len = rest.strip_prefix('{')?.find('}')?;
+	if !(1..=6).contains(&hex_len) {
+		return None;
+	}
+	let code = u32::from_str_radix(&rest[1..1 + hex_len], 16).ok()?;
+
+	Some((char::from_u32(code)?, hex_len + 2))
+}
+
+/// Decodes the `&quot;`, `&apos;`, `&amp;`, `&lt;`, and `&gt;` entities, and the decimal (`&#34;`)
+/// and hexadecimal (`&#x22;`) character references. Unknown or invalid entities are kept.
+fn decode_entities(value: &str) -> String {
+	let mut decoded = String::with_capacity(value.len());
+	let mut rest = value;
+	while let Some(amp_idx) = rest.find('&') {
+		decoded.push_str(&rest[..amp_idx]);
+		rest = &rest[amp_idx..];
+
+		let entity = rest
+			.find(';')
+			.and_then(|semi_idx| Some((semi_idx, decode_entity(&rest[1..semi_idx])?)));
+		match entity {
+			Some((semi_idx, c)) => {
+				decoded.push(c);
+				rest = &rest[semi_idx + 1..];
+			}
+			None => {
+				decoded.push('&');
+				rest = &rest[1..];
+			}
 		}
 	}
+	decoded.push_str(rest);
+	decoded
+}
 
-	if attrs.is_empty() { None } else { Some(attrs) }
+/// Returns the character of an entity name without its `&` and `;` (e.g., `quot` or `#x22`).
+fn decode_entity(name: &str) -> Option<char> {
+	match name {
+		"quot" => Some('"'),
+		"apos" => Some('\''),
+		"amp" => Some('&'),
+		"lt" => Some('<'),
+		"gt" => Some('>'),
+		_ => {
+			let code = name.strip_prefix('#')?;
+			let code = match code.strip_prefix(['x', 'X']) {
+				Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+				None => code.parse().ok()?,
+			};
+			char::from_u32(code)
+		}
+	}
 }
 
+// endregion: --- Support
+
 // region:    --- Tests
 
 #[path = "attrs_parser_tests.rs"]
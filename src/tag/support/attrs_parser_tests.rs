@@ -1,8 +1,9 @@
 //! Tests for the attribute parsing logic in `attrs_parser.rs`.
 
-use crate::tag::support::parse_attrs_ref;
+use crate::tag::support::{parse_attr_spans, parse_attrs_ref};
 
-use std::collections::HashMap;
+use crate::tag::{AttrDecoding, Attrs, AttrsRef};
+use std::borrow::Cow;
 
 type Result<T> = core::result::Result<T, Box<dyn std::error::Error>>;
 
@@ -15,10 +16,9 @@ fn test_support_tag_attrs_parser_simple() -> Result<()> {
 	let parsed = parse_attribute(Some(raw));
 
 	// -- Check
-	let mut expected = HashMap::new();
-	expected.insert("path".to_string(), "a/b.txt".to_string());
-	expected.insert("id".to_string(), "123".to_string());
-	expected.insert("flag".to_string(), "".to_string());
+	let expected = [("path", Some("a/b.txt")), ("id", Some("123")), ("flag", None)]
+		.into_iter()
+		.collect::<Attrs>();
 
 	assert_eq!(parsed, Some(expected));
 
@@ -31,13 +31,12 @@ fn test_support_tag_attrs_parser_ref_simple() -> Result<()> {
 	let raw = r#"path="a/b.txt" id=123 flag"#;
 
 	// -- Exec
-	let parsed = parse_attrs_ref(Some(raw));
+	let parsed = parse_attrs_ref(Some(raw), AttrDecoding::Raw);
 
 	// -- Check
-	let mut expected = HashMap::new();
-	expected.insert("path", "a/b.txt");
-	expected.insert("id", "123");
-	expected.insert("flag", "");
+	let expected = [("path", Some("a/b.txt")), ("id", Some("123")), ("flag", None)]
+		.into_iter()
+		.collect::<AttrsRef>();
 
 	assert_eq!(parsed, Some(expected));
 
@@ -53,11 +52,14 @@ fn test_support_tag_attrs_parser_handles_spacing() -> Result<()> {
 	let parsed = parse_attribute(Some(raw));
 
 	// -- Check
-	let mut expected = HashMap::new();
-	expected.insert("key".to_string(), "value with spaces".to_string());
-	expected.insert("other".to_string(), "true".to_string());
-	expected.insert("line".to_string(), "42".to_string());
-	expected.insert("flag".to_string(), "".to_string());
+	let expected = [
+		("key", Some("value with spaces")),
+		("other", Some("true")),
+		("line", Some("42")),
+		("flag", None),
+	]
+	.into_iter()
+	.collect::<Attrs>();
 
 	assert_eq!(parsed, Some(expected));
 
@@ -81,18 +83,113 @@ fn test_support_tag_attrs_parser_none_or_empty() -> Result<()> {
 	Ok(())
 }
 
+#[test]
+fn test_support_tag_attrs_parser_spans() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"<FILE path="a.rs"  flag id = 12 empty='' dup=1 dup=2 >"#;
+	let raw = &input[5..input.len() - 1];
+
+	// -- Exec
+	let spans = parse_attr_spans(raw, 5, AttrDecoding::Raw);
+
+	// -- Check
+	let attrs: Vec<(&str, Option<&str>)> = spans
+		.iter()
+		.map(|span| (&input[span.key.range()], span.value.map(|value| &input[value.range()])))
+		.collect();
+	assert_eq!(
+		attrs,
+		[
+			("path", Some("a.rs")),
+			("flag", None),
+			("id", Some("12")),
+			("empty", Some("")),
+			("dup", Some("1")),
+			("dup", Some("2")),
+		]
+	);
+	assert_eq!(spans[0].value.map(|value| value.start), Some(12));
+	assert!(spans[3].value.is_some_and(|value| value.is_empty() && value.start == 39));
+
+	Ok(())
+}
+
+#[test]
+fn test_support_tag_attrs_parser_ref_keeps_order_and_duplicates() -> Result<()> {
+	// -- Setup & Fixtures
+	let raw = r#"include="a.md" mode=x include='b.md'"#;
+
+	// -- Exec
+	let parsed = parse_attrs_ref(Some(raw), AttrDecoding::Raw).ok_or("should parse attributes")?;
+
+	// -- Check
+	let entries: Vec<(&str, Option<&str>)> = parsed.iter().collect();
+	assert_eq!(
+		entries,
+		[("include", Some("a.md")), ("mode", Some("x")), ("include", Some("b.md"))]
+	);
+	assert_eq!(parsed.len(), 3);
+	assert_eq!(parsed.get("include"), Some("b.md"));
+	assert_eq!(parsed.get_all("include").collect::<Vec<_>>(), ["a.md", "b.md"]);
+	assert!(parsed.contains_key("mode"));
+	assert_eq!(parsed.get("missing"), None);
+
+	Ok(())
+}
+
+#[test]
+fn test_support_tag_attrs_parser_backslash_decoding() -> Result<()> {
+	// -- Setup & Fixtures
+	let raw = r#"title="say \"hi\"" msg='it\'s\nok' path="C:\dir" plain=x cmp="a \u{3e} b \u{zz} \u{}""#;
+
+	// -- Exec
+	let parsed = parse_attrs_ref(Some(raw), AttrDecoding::Backslash).ok_or("should parse attributes")?;
+	let raw_parsed = parse_attrs_ref(Some(raw), AttrDecoding::Raw).ok_or("should parse attributes")?;
+	let spans = parse_attr_spans(raw, 0, AttrDecoding::Backslash);
+
+	// -- Check
+	assert_eq!(parsed.len(), 5);
+	assert_eq!(parsed.get("title"), Some(r#"say "hi""#));
+	assert_eq!(parsed.get("msg"), Some("it's\nok"));
+	assert_eq!(parsed.get("path"), Some(r"C:\dir"));
+	assert!(matches!(parsed.get_cow("plain"), Some(Cow::Borrowed("x"))));
+	assert_eq!(parsed.get("cmp"), Some(r"a > b \u{zz} \u{}"));
+	assert!(matches!(parsed.get_cow("title"), Some(Cow::Owned(_))));
+	assert_eq!(spans[0].value.map(|value| &raw[value.range()]), Some(r#"say \"hi\""#));
+	// The raw mode ends the value at the escaped quote.
+	assert_eq!(raw_parsed.get("title"), Some("say \\"));
+
+	Ok(())
+}
+
+#[test]
+fn test_support_tag_attrs_parser_entities_decoding() -> Result<()> {
+	// -- Setup & Fixtures
+	let raw = r#"cmd="echo &quot;a &amp;&amp; b&quot; &gt; out" code="&#60;&#x3E;" odd="&nope; & &#xZZ;" plain=x"#;
+
+	// -- Exec
+	let parsed = parse_attrs_ref(Some(raw), AttrDecoding::Entities).ok_or("should parse attributes")?;
+
+	// -- Check
+	assert_eq!(parsed.get("cmd"), Some(r#"echo "a && b" > out"#));
+	assert_eq!(parsed.get("code"), Some("<>"));
+	assert_eq!(parsed.get("odd"), Some("&nope; & &#xZZ;"));
+	assert!(matches!(parsed.get_cow("plain"), Some(Cow::Borrowed("x"))));
+
+	Ok(())
+}
+
 // region:    --- Support
 
-/// Parses a raw string of attributes (key=value pairs) into a HashMap of owned strings.
+/// Parses a raw string of attributes (key=value pairs) into owned `Attrs`.
 ///
 /// Supports unquoted values, single-quoted values, and double-quoted values.
 /// Flag attributes (key without value) are stored with an empty string value.
 ///
 /// Examples: `path="a/b.txt" id=123 flag`
-pub fn parse_attribute(attrs_raw: Option<&str>) -> Option<HashMap<String, String>> {
-	let refs = parse_attrs_ref(attrs_raw)?;
-	let owned = refs.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
-	Some(owned)
+pub fn parse_attribute(attrs_raw: Option<&str>) -> Option<Attrs> {
+	let refs = parse_attrs_ref(attrs_raw, AttrDecoding::Raw)?;
+	Some(Attrs::from(refs))
 }
 
 // endregion: --- Support
@@ -0,0 +1,190 @@
+//! Writing of elements back to markup under a fence.
+
+use crate::Result;
+use crate::tag::support::invalid_attr;
+use crate::tag::{AttrDecoding, Part, TagElem, TagFence, TagPattern};
+use std::borrow::Cow;
+use std::fmt::Write;
+
+/// Appends the markup of the element to `out`, as a self-closing tag when the content is empty,
+/// and as a paired tag otherwise.
+///
+/// Attribute values are quoted and escaped so that extracting with `decoding` returns them unchanged.
+/// Fails with `Error::InvalidAttr` for a raw value that cannot be written unescaped (see `write_attr_value`).
+pub fn write_elem_markup(out: &mut String, elem: &TagElem, fence: &TagFence, decoding: AttrDecoding) -> Result<()> {
+	let delims: Vec<&str> = [fence.open_delim, fence.close_delim]
+		.into_iter()
+		.chain(fence.close_delim_alts.unwrap_or_default().iter().copied())
+		.collect();
+
+	out.push_str(fence.open_delim);
+	out.push_str(&elem.tag);
+	for (key, value) in elem.attrs.iter().flat_map(|attrs| attrs.iter()) {
+		out.push(' ');
+		out.push_str(key);
+		if let Some(value) = value {
+			out.push('=');
+			write_attr_value(out, value, &delims, decoding)
+				.map_err(|cause| invalid_attr(&elem.tag, key, value, cause))?;
+		}
+	}
+
+	if elem.content.is_empty() {
+		out.push_str(fence.self_closing_suffix);
+		out.push_str(fence.close_delim);
+		return Ok(());
+	}
+
+	out.push_str(fence.close_delim);
+	out.push_str(&elem.content);
+	out.push_str(fence.open_delim);
+	out.push_str(fence.closing_tag_prefix);
+	out.push_str(&elem.tag);
+	out.push_str(fence.close_delim);
+
+	Ok(())
+}
+
+/// Returns the first element whose content contains one of its own closing tags under the fence,
+/// which would end the element early when extracted again.
+pub fn find_closing_collision<'e>(
+	elems: impl IntoIterator<Item = &'e TagElem>,
+	fence: &TagFence,
+) -> Option<&'e TagElem> {
+	elems.into_iter().find(|elem| {
+		TagPattern::new(&elem.tag, *fence)
+			.end_tags
+			.iter()
+			.any(|end_tag| elem.content.contains(end_tag.as_str()))
+	})
+}
+
+/// Returns the tag name at the first position where the extracted parts differ from the source parts,
+/// or `None` when they match.
+///
+/// Adjacent text parts are merged on both sides first, since extraction returns a text span as one part
+/// (e.g., the split text returned by `TagStreamParser`). Elements are compared by tag name, attributes,
+/// and content, ignoring their spans and whether they were auto-closed.
+/// The name is empty when the first difference is between text parts.
+pub fn find_round_trip_mismatch(source: &[Part], extracted: &[Part]) -> Option<String> {
+	let source = merge_texts(source);
+	let extracted = merge_texts(extracted);
+	let tag_name_at = |idx: usize| {
+		[source.get(idx), extracted.get(idx)]
+			.into_iter()
+			.flatten()
+			.find_map(|part| match part {
+				MergedPart::TagElem(elem) => Some(elem.tag.clone()),
+				MergedPart::Text(_) => None,
+			})
+			.unwrap_or_default()
+	};
+
+	let mismatch_idx = source
+		.iter()
+		.zip(&extracted)
+		.position(|pair| match pair {
+			(MergedPart::Text(source_text), MergedPart::Text(extracted_text)) => source_text != extracted_text,
+			(MergedPart::TagElem(source_elem), MergedPart::TagElem(extracted_elem)) => {
+				source_elem.tag != extracted_elem.tag
+					|| source_elem.attrs != extracted_elem.attrs
+					|| source_elem.content != extracted_elem.content
+			}
+			_ => true,
+		})
+		.or_else(|| (source.len() != extracted.len()).then(|| source.len().min(extracted.len())))?;
+
+	Some(tag_name_at(mismatch_idx))
+}
+
+// region:    --- Support
+
+/// A part where adjacent text parts are merged into one text.
+enum MergedPart<'p> {
+	Text(Cow<'p, str>),
+	TagElem(&'p TagElem),
+}
+
+fn merge_texts(parts: &[Part]) -> Vec<MergedPart<'_>> {
+	let mut merged: Vec<MergedPart> = Vec::new();
+	for part in parts {
+		match (merged.last_mut(), part) {
+			(Some(MergedPart::Text(last)), Part::Text(text)) => last.to_mut().push_str(text),
+			(_, Part::Text(text)) => merged.push(MergedPart::Text(Cow::Borrowed(text))),
+			(_, Part::TagElem(elem)) => merged.push(MergedPart::TagElem(elem)),
+		}
+	}
+	merged
+}
+
+/// Appends the quoted value, escaped for `decoding`, where `delims` are the delimiters of the fence
+/// that would end the opening tag or start another tag inside the value.
+///
+/// Backslash and entities values escape the first character of each delimiter (e.g., `\u{3e}` or `&#x3e;`).
+/// Raw values cannot be escaped, so they use double quotes unless the value contains one, and single quotes
+/// otherwise. Fails with the cause for a raw value containing both quotes or a delimiter.
+fn write_attr_value(
+	out: &mut String,
+	value: &str,
+	delims: &[&str],
+	decoding: AttrDecoding,
+) -> core::result::Result<(), &'static str> {
+	let starts_delim = |idx: usize| delims.iter().any(|delim| value[idx..].starts_with(delim));
+
+	match decoding {
+		AttrDecoding::Raw => {
+			if value.contains('"') && value.contains('\'') {
+				return Err("a raw value cannot contain both quote characters");
+			}
+			if delims.iter().any(|delim| value.contains(delim)) {
+				return Err("a raw value cannot contain a delimiter of the fence");
+			}
+			let quote = if value.contains('"') { '\'' } else { '"' };
+			out.push(quote);
+			out.push_str(value);
+			out.push(quote);
+		}
+		AttrDecoding::Backslash => {
+			out.push('"');
+			for (idx, c) in value.char_indices() {
+				if starts_delim(idx) {
+					let _ = write!(out, "\\u{{{:x}}}", c as u32);
+					continue;
+				}
+				if matches!(c, '\\' | '"') {
+					out.push('\\');
+				}
+				out.push(c);
+			}
+			out.push('"');
+		}
+		AttrDecoding::Entities => {
+			out.push('"');
+			for (idx, c) in value.char_indices() {
+				match c {
+					'&' => out.push_str("&amp;"),
+					'"' => out.push_str("&quot;"),
+					'<' => out.push_str("&lt;"),
+					'>' => out.push_str("&gt;"),
+					c if starts_delim(idx) => {
+						let _ = write!(out, "&#x{:x};", c as u32);
+					}
+					c => out.push(c),
+				}
+			}
+			out.push('"');
+		}
+	}
+
+	Ok(())
+}
+
+// endregion: --- Support
+
+// region:    --- Tests
+
+#[path = "markup_writer_tests.rs"]
+#[cfg(test)]
+mod tests;
+
+// endregion: --- Tests
@@ -0,0 +1,254 @@
+//! Tests for the markup writing logic in `markup_writer.rs`.
+
+use crate::Error;
+use crate::tag::{
+	AttrDecoding, Attrs, FENCE_BRACKETS, FENCE_XML, Part, Parts, TagElem, TagOptions, TagStreamParser, extract,
+};
+
+type Result<T> = core::result::Result<T, Box<dyn std::error::Error>>;
+
+#[test]
+fn test_support_tag_markup_writer_paired_and_self_closing() -> Result<()> {
+	// -- Setup & Fixtures
+	let attrs = [("path", Some("a.rs")), ("overwrite", None)].into_iter().collect::<Attrs>();
+	let file = TagElem::new("FILE", Some(attrs), "fn a() {}");
+	let delete = TagElem::new("DELETE", Some(Attrs::from([("path", "b.rs")])), "");
+
+	// -- Exec & Check
+	assert_eq!(
+		file.to_markup(&FENCE_XML)?,
+		r#"<FILE path="a.rs" overwrite>fn a() {}</FILE>"#
+	);
+	assert_eq!(delete.to_markup(&FENCE_XML)?, r#"<DELETE path="b.rs"/>"#);
+	assert_eq!(
+		file.to_markup(&FENCE_BRACKETS)?,
+		r#"[[[FILE path="a.rs" overwrite]]]fn a() {}[[[/FILE]]]"#
+	);
+	assert_eq!(delete.to_markup(&FENCE_BRACKETS)?, r#"[[[DELETE path="b.rs"/]]]"#);
+
+	Ok(())
+}
+
+#[test]
+fn test_support_tag_markup_writer_raw_quoting() -> Result<()> {
+	// -- Setup & Fixtures
+	let attrs = Attrs::from([("msg", r#"say "hi""#), ("note", "it's"), ("empty", "")]);
+	let elem = TagElem::new("COMMIT", Some(attrs.clone()), "body");
+
+	// -- Exec
+	let markup = elem.to_markup(&FENCE_XML)?;
+	let parts = extract(&markup, &["COMMIT"], None);
+
+	// -- Check
+	assert_eq!(markup, r#"<COMMIT msg='say "hi"' note="it's" empty="">body</COMMIT>"#);
+	assert_eq!(parts.tag_elems()[0].attrs, Some(attrs));
+
+	Ok(())
+}
+
+#[test]
+fn test_support_tag_markup_writer_raw_unrepresentable_values() -> Result<()> {
+	// -- Setup & Fixtures
+	let cases = [
+		(FENCE_XML, "a > b"),
+		(FENCE_XML, "see <FILE>"),
+		(FENCE_BRACKETS, "a]]b"),
+		(FENCE_BRACKETS, r#"both " and '"#),
+	];
+
+	for (fence, value) in cases {
+		let elem = TagElem::new("CMP", Some(Attrs::from([("expr", value)])), "body");
+
+		// -- Exec
+		let res = elem.to_markup(&fence);
+
+		// -- Check
+		match res {
+			Err(Error::InvalidAttr {
+				tag_name,
+				key,
+				value: attr_value,
+				..
+			}) => {
+				assert_eq!(
+					(tag_name.as_str(), key.as_str(), attr_value.as_str()),
+					("CMP", "expr", value)
+				);
+			}
+			other => return Err(format!("{value:?} should be an invalid attr, got {other:?}").into()),
+		}
+	}
+
+	Ok(())
+}
+
+#[test]
+fn test_support_tag_markup_writer_escaped_values_round_trip() -> Result<()> {
+	// -- Setup & Fixtures
+	let values = [
+		"a > b",
+		"a < b",
+		"x]]b",
+		"[[[NOTE]]] and ]]]",
+		"see <FILE>x</FILE>",
+		r#"both "double" and 'single'"#,
+		r"back\slash \u{3e} \n",
+		"&amp; &#x3e; &",
+		"line\nbreak",
+		"",
+		"é ü",
+	];
+
+	for fence in [FENCE_XML, FENCE_BRACKETS] {
+		for decoding in [AttrDecoding::Backslash, AttrDecoding::Entities] {
+			let options = TagOptions::default().with_fence(fence).with_attr_decoding(decoding);
+			for value in values {
+				let elem = TagElem::new("FILE", Some(Attrs::from([("v", value)])), "body");
+				let parts = Parts::new(vec![Part::TagElem(elem.clone())]);
+
+				// -- Exec
+				let rendered = parts.render(&options)?;
+				let reparsed = extract(&rendered, &["FILE"], options);
+
+				// -- Check
+				assert_eq!(reparsed.tag_elems(), [&elem], "{} {decoding:?}: {rendered}", fence.name);
+			}
+		}
+	}
+
+	Ok(())
+}
+
+#[test]
+fn test_support_tag_markup_writer_render_round_trip() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"Intro
+<FILE path="a.rs" msg="both \"double\" and 'single' \\ quotes">fn a() {}</FILE>
+<DELETE path="b.rs"/>
+Outro"#;
+	let names = ["FILE", "DELETE"];
+	let source_options = TagOptions::default()
+		.with_capture_text(true)
+		.with_attr_decoding(AttrDecoding::Backslash);
+	let parts = extract(input, &names, source_options);
+	let msg = parts.tag_elems()[0].attrs.as_ref().and_then(|attrs| attrs.get("msg"));
+	assert_eq!(msg, Some(r#"both "double" and 'single' \ quotes"#));
+
+	for decoding in [AttrDecoding::Backslash, AttrDecoding::Entities] {
+		let options = source_options.with_fence(FENCE_BRACKETS).with_attr_decoding(decoding);
+
+		// -- Exec
+		let rendered = parts.render(&options)?;
+		let reparsed = extract(&rendered, &names, options);
+
+		// -- Check
+		assert_eq!(reparsed.texts(), parts.texts());
+		assert_eq!(elem_values(&reparsed), elem_values(&parts), "{decoding:?}: {rendered}");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn test_support_tag_markup_writer_checked_picks_next_fence() -> Result<()> {
+	// -- Setup & Fixtures
+	let elem = TagElem::new(
+		"FILE",
+		Some(Attrs::from([("path", "prompt.md")])),
+		"Answer in <FILE>...</FILE>.",
+	);
+
+	// -- Exec
+	let (markup, fence) = elem.to_markup_checked(&[FENCE_XML, FENCE_BRACKETS])?;
+
+	// -- Check
+	assert_eq!(fence, FENCE_BRACKETS);
+	let options = TagOptions::default().with_fence(fence);
+	let parts = extract(&markup, &["FILE"], options);
+	assert_eq!(parts.tag_elems()[0].content, elem.content);
+
+	Ok(())
+}
+
+#[test]
+fn test_support_tag_markup_writer_checked_collision_error() -> Result<()> {
+	// -- Setup & Fixtures
+	let elem = TagElem::new("FILE", None, "Both </FILE> and [[[/FILE]]] appear.");
+	let parts = Parts::from(vec![Part::Text("Intro ".to_string()), Part::TagElem(elem.clone())]);
+
+	// -- Exec
+	let elem_res = elem.to_markup_checked(&[FENCE_XML, FENCE_BRACKETS]);
+	let parts_res = parts.render_checked(&TagOptions::default(), &[FENCE_XML, FENCE_BRACKETS]);
+
+	// -- Check
+	for res in [elem_res, parts_res] {
+		match res {
+			Err(Error::RenderCollision { tag_name, fences }) => {
+				assert_eq!(tag_name, "FILE");
+				assert_eq!(fences, ["xml", "brackets"]);
+			}
+			other => return Err(format!("should be a render collision, got {other:?}").into()),
+		}
+	}
+
+	Ok(())
+}
+
+#[test]
+fn test_support_tag_markup_writer_checked_round_trip_mismatch() -> Result<()> {
+	// -- Setup & Fixtures
+	// A raw value with a '>' cannot be written under the XML fence, though no closing tag collides.
+	let elem = TagElem::new("CMP", Some(Attrs::from([("expr", "a > b")])), "body");
+
+	// -- Exec
+	let xml_res = elem.to_markup_checked(&[FENCE_XML]);
+	let (_, fence) = elem.to_markup_checked(&[FENCE_XML, FENCE_BRACKETS])?;
+
+	// -- Check
+	assert!(matches!(xml_res, Err(Error::RenderCollision { tag_name, .. }) if tag_name == "CMP"));
+	assert_eq!(fence, FENCE_BRACKETS);
+
+	Ok(())
+}
+
+#[test]
+fn test_support_tag_markup_writer_checked_stream_parser_parts() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "Intro text <FILE path=\"a.rs\">fn a() {}</FILE> middle text <DELETE path=\"b.rs\"/> outro text";
+	let options = TagOptions::default().with_capture_text(true);
+	let mut parser = TagStreamParser::new(&["FILE", "DELETE"], options);
+	let mut stream_parts = Vec::new();
+	for chunk in input.as_bytes().chunks(3) {
+		stream_parts.extend(parser.push(std::str::from_utf8(chunk)?));
+	}
+	stream_parts.extend(parser.finish());
+	let parts: Parts = stream_parts.into_iter().collect();
+	assert!(
+		parts
+			.parts()
+			.windows(2)
+			.any(|pair| matches!(pair, [Part::Text(_), Part::Text(_)])),
+		"the stream parser should split a text span"
+	);
+
+	// -- Exec
+	let (rendered, fence) = parts.render_checked(&options, &[FENCE_XML])?;
+
+	// -- Check
+	assert_eq!(fence, FENCE_XML);
+	assert_eq!(rendered, input);
+
+	Ok(())
+}
+
+// region:    --- Support
+
+fn elem_values(parts: &Parts) -> Vec<(&str, Option<&Attrs>, &str)> {
+	parts
+		.tag_elems()
+		.into_iter()
+		.map(|elem| (elem.tag.as_str(), elem.attrs.as_ref(), elem.content.as_str()))
+		.collect()
+}
+
+// endregion: --- Support
@@ -1,7 +1,13 @@
 // region:    --- Modules
 
 mod attrs_parser;
+mod markup_writer;
+mod tag_scanner;
+mod typed_attrs;
 
-pub use attrs_parser::parse_attrs_ref;
+pub use attrs_parser::{parse_attr_spans, parse_attrs_ref};
+pub(crate) use markup_writer::*;
+pub(crate) use tag_scanner::*;
+pub(crate) use typed_attrs::*;
 
 // endregion: --- Modules
@@ -0,0 +1,848 @@
+//! Scanning primitives shared by the tag iterators and the stream parser.
+//!
+//! Every search takes a `complete` flag. When `complete` is `false`, the input is a prefix of a
+//! longer input that is still being received, so any match that more input could change is reported
+//! as pending rather than decided.
+
+use crate::tag::support::{parse_attr_spans, parse_attrs_ref};
+use crate::tag::{
+	AttrDecoding, RawSection, TagElemRef, TagElemSpans, TagFence, TagNameMatcher, TagOptions, TagPattern, TagSpan,
+};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+/// The resolved end boundary of an element's content.
+pub(crate) enum ElemEnd {
+	/// A matching closing tag starting at `start_idx`, spanning `len` bytes.
+	Close { start_idx: usize, len: usize },
+
+	/// A configured opening tag starting at `start_idx`, which synthesizes a close (auto-close).
+	Opening { start_idx: usize },
+
+	/// The end of the input, which synthesizes a close (auto-close).
+	Eof,
+
+	/// No closing boundary exists in the complete input.
+	Unclosed,
+
+	/// The boundary cannot be decided until more input is received.
+	Pending,
+}
+
+/// The boundaries of a parsed opening tag.
+pub(crate) struct OpenTag {
+	/// The byte index where the close delimiter of the opening tag starts.
+	pub close_start_idx: usize,
+
+	/// The byte index of the last character of the opening tag.
+	pub end_idx: usize,
+
+	/// Whether the opening tag ends with the self-closing suffix.
+	pub self_closing: bool,
+}
+
+/// The resumable state of a search for the end of an element's content.
+pub(crate) struct ElemEndSearch {
+	/// The byte index from which the search continues.
+	pub search_idx: usize,
+
+	/// The number of nested same-name elements currently open.
+	pub depth: usize,
+
+	/// The heredoc-style terminator declared by the opening tag, whose line ends the element instead of a closing tag.
+	pub terminator: Option<String>,
+
+	/// The byte index of the open delimiter of the raw section being skipped, whose close delimiter is searched
+	/// from `search_idx`.
+	pub raw_open_idx: Option<usize>,
+
+	/// The byte index of the line checked against the terminator, whose line break is searched from `search_idx`.
+	/// It is `None` until the line of the opening tag ends.
+	pub line_start_idx: Option<usize>,
+}
+
+/// Accessors
+impl ElemEndSearch {
+	/// Returns the byte index before which the content cannot be part of the closing boundary.
+	pub fn decided_idx(&self) -> usize {
+		self.line_start_idx.unwrap_or(self.search_idx)
+	}
+}
+
+/// Precomputed tag patterns and parsing rules shared by the extraction APIs.
+pub(crate) struct TagScanner {
+	tag_patterns: Vec<TagPattern>,
+	/// The configured pattern indices by the first byte of their name (lowercase when names are
+	/// case-insensitive), longest name first, so each opening delimiter is resolved with one lookup.
+	patterns_by_first_byte: HashMap<u8, Vec<usize>>,
+	fence: TagFence,
+	/// The canonical and alternate delimiters that end opening and closing tags.
+	close_delims: Vec<&'static str>,
+	/// The non-exact matchers that names found by scanning for any well-formed name must satisfy.
+	name_matchers: Vec<TagNameMatcher>,
+	wildcard: bool,
+	auto_close: bool,
+	nested: bool,
+	/// Whether tag names match regardless of ASCII case.
+	ignore_case: bool,
+	attr_decoding: AttrDecoding,
+	/// The attribute declaring a heredoc-style terminator, if any.
+	terminator_attr: Option<&'static str>,
+	/// The delimiters of raw sections skipped when searching for the end of an element, if any.
+	raw_section: Option<RawSection>,
+	/// The maximum number of bytes needed from a start index to decide a match starting there.
+	lookahead: usize,
+}
+
+/// Constructors
+impl TagScanner {
+	pub fn new(tag_names: &[&str], options: TagOptions) -> Self {
+		Self::new_with_parts(tag_names, Vec::new(), options)
+	}
+
+	/// Creates a scanner where exact matchers become configured names and the others match discovered names.
+	pub fn new_with_matchers(matchers: &[TagNameMatcher], options: TagOptions) -> Self {
+		let mut tag_names = Vec::new();
+		let mut name_matchers = Vec::new();
+		for matcher in matchers {
+			match matcher {
+				TagNameMatcher::Exact(name) => tag_names.push(name.as_str()),
+				_ => name_matchers.push(matcher.clone()),
+			}
+		}
+
+		Self::new_with_parts(&tag_names, name_matchers, options)
+	}
+
+	fn new_with_parts(tag_names: &[&str], name_matchers: Vec<TagNameMatcher>, options: TagOptions) -> Self {
+		let fence = options.fence_or_default();
+		let tag_patterns: Vec<TagPattern> = tag_names.iter().map(|&name| TagPattern::new(name, fence)).collect();
+		let close_delims: Vec<&'static str> = [fence.close_delim]
+			.into_iter()
+			.chain(fence.close_delim_alts.unwrap_or_default().iter().copied())
+			.collect();
+
+		// An opening tag needs at least the opening delimiter, one name character, and the token after the name.
+		let after_name_len = close_delims
+			.iter()
+			.map(|close_delim| close_delim.len())
+			.chain([fence.closing_tag_prefix.len(), 1])
+			.max()
+			.unwrap_or(1);
+		let lookahead = tag_patterns
+			.iter()
+			.map(TagPattern::lookahead)
+			.chain([fence.open_delim.len() + 1 + after_name_len])
+			.chain(fence.raw_section.map(|raw_section| raw_section.open.len()))
+			.max()
+			.unwrap_or(0);
+
+		let ignore_case = options.case_insensitive_names;
+		let mut patterns_by_first_byte: HashMap<u8, Vec<usize>> = HashMap::new();
+		for (pattern_idx, tag_info) in tag_patterns.iter().enumerate() {
+			if let Some(&first_byte) = tag_info.name.as_bytes().first() {
+				let key = if ignore_case {
+					first_byte.to_ascii_lowercase()
+				} else {
+					first_byte
+				};
+				patterns_by_first_byte.entry(key).or_default().push(pattern_idx);
+			}
+		}
+		for pattern_idxs in patterns_by_first_byte.values_mut() {
+			pattern_idxs.sort_by_key(|&pattern_idx| std::cmp::Reverse(tag_patterns[pattern_idx].name.len()));
+		}
+
+		TagScanner {
+			tag_patterns,
+			patterns_by_first_byte,
+			fence,
+			close_delims,
+			name_matchers,
+			wildcard: options.wildcard,
+			auto_close: options.auto_close,
+			nested: options.nested,
+			ignore_case,
+			attr_decoding: options.attr_decoding,
+			terminator_attr: options.terminator_attr,
+			raw_section: fence.raw_section,
+			lookahead,
+		}
+	}
+}
+
+/// Accessors
+impl TagScanner {
+	pub fn fence(&self) -> &TagFence {
+		&self.fence
+	}
+}
+
+/// Scanning
+impl TagScanner {
+	/// Returns whether a match starting at `idx` cannot change when more input is appended.
+	pub fn is_decided(&self, input: &str, idx: usize, complete: bool) -> bool {
+		is_decided_within(input, idx, complete, self.lookahead)
+	}
+
+	/// Returns the index from which a search over a partial input must resume once more input arrives.
+	pub fn resume_idx(&self, input: &str, from_idx: usize) -> usize {
+		self.resume_idx_within(input, from_idx, self.lookahead)
+	}
+
+	fn resume_idx_within(&self, input: &str, from_idx: usize, lookahead: usize) -> usize {
+		let resume_idx = from_idx.max(input.len().saturating_sub(lookahead));
+		let resume_idx = match self.find_discovered_tail(input, from_idx) {
+			Some(tail_start_idx) => resume_idx.min(tail_start_idx),
+			None => resume_idx,
+		};
+
+		input.floor_char_boundary(resume_idx)
+	}
+
+	/// Finds the next valid opening tag, ignoring tags named `exclude_name`, if any.
+	///
+	/// Openings are found in a single pass over the opening delimiters. At each delimiter, the longest
+	/// configured name wins, then a discovered name is tried when no configured opening is valid there.
+	///
+	/// Returns the start index of the opening tag and its pattern, which is built for discovered names.
+	pub fn find_next_opening(
+		&self,
+		input: &str,
+		from_idx: usize,
+		exclude_name: Option<&str>,
+	) -> Option<(usize, Cow<'_, TagPattern>)> {
+		let open_delim = self.fence.open_delim;
+		let mut current_pos = from_idx;
+
+		while let Some(offset) = input.get(current_pos..)?.find(open_delim) {
+			let start_idx = current_pos + offset;
+			if let Some(tag_info) = self.opening_at(input, start_idx, exclude_name) {
+				return Some((start_idx, tag_info));
+			}
+			current_pos = input.ceil_char_boundary(start_idx + 1);
+		}
+
+		None
+	}
+
+	/// Finds the next valid opening tag like `find_next_opening`, ignoring the openings inside raw sections.
+	///
+	/// A raw section without its close delimiter is plain text, like in `find_element_end`.
+	pub fn find_next_opening_outside_raw(&self, input: &str, from_idx: usize) -> Option<(usize, Cow<'_, TagPattern>)> {
+		let Some(raw_section) = self.raw_section else {
+			return self.find_next_opening(input, from_idx, None);
+		};
+		let mut current_pos = from_idx;
+
+		loop {
+			let Some(raw_open_idx) = input
+				.get(current_pos..)?
+				.find(raw_section.open)
+				.map(|offset| current_pos + offset)
+			else {
+				return self.find_next_opening(input, current_pos, None);
+			};
+			if let Some(opening) = self.find_next_opening(&input[..raw_open_idx], current_pos, None) {
+				return Some(opening);
+			}
+
+			let body_idx = raw_open_idx + raw_section.open.len();
+			let Some(close_offset) = input[body_idx..].find(raw_section.close) else {
+				return self.find_next_opening(input, raw_open_idx, None);
+			};
+			current_pos = body_idx + close_offset + raw_section.close.len();
+		}
+	}
+
+	/// Returns the pattern of the valid opening tag starting at the opening delimiter at `start_idx`, if any.
+	fn opening_at(&self, input: &str, start_idx: usize, exclude_name: Option<&str>) -> Option<Cow<'_, TagPattern>> {
+		let name_start_idx = start_idx + self.fence.open_delim.len();
+
+		if let Some(tag_info) = self.match_configured_name(input, name_start_idx, exclude_name)
+			&& self.is_valid_after_name(input, name_start_idx + tag_info.name.len())
+		{
+			return Some(Cow::Borrowed(tag_info));
+		}
+
+		if self.discovers_names()
+			&& let Some(name) = self.match_discovered_name(input, name_start_idx, exclude_name)
+		{
+			return Some(Cow::Owned(TagPattern::new(name, self.fence)));
+		}
+
+		None
+	}
+
+	/// Returns the longest configured pattern whose name starts at `name_start_idx`, ignoring `exclude_name`.
+	fn match_configured_name(
+		&self,
+		input: &str,
+		name_start_idx: usize,
+		exclude_name: Option<&str>,
+	) -> Option<&TagPattern> {
+		let remaining_input = input.get(name_start_idx..)?.as_bytes();
+		let first_byte = *remaining_input.first()?;
+		let key = if self.ignore_case {
+			first_byte.to_ascii_lowercase()
+		} else {
+			first_byte
+		};
+
+		self.patterns_by_first_byte
+			.get(&key)?
+			.iter()
+			.map(|&pattern_idx| &self.tag_patterns[pattern_idx])
+			.filter(|tag_info| exclude_name.is_none_or(|exclude_name| !self.names_eq(exclude_name, &tag_info.name)))
+			.find(|tag_info| {
+				remaining_input
+					.get(..tag_info.name.len())
+					.is_some_and(|name| self.names_eq_bytes(name, tag_info.name.as_bytes()))
+			})
+	}
+
+	/// Returns the well-formed name starting at `name_start_idx` if it is accepted as a discovered opening.
+	fn match_discovered_name<'i>(
+		&self,
+		input: &'i str,
+		name_start_idx: usize,
+		exclude_name: Option<&str>,
+	) -> Option<&'i str> {
+		let remaining_input = input.get(name_start_idx..)?;
+		let name = &remaining_input[..well_formed_name_len(remaining_input)];
+
+		let accepted = !name.is_empty()
+			&& exclude_name.is_none_or(|exclude_name| !self.names_eq(exclude_name, name))
+			&& self.accepts_discovered_name(name)
+			&& self.is_valid_after_name(input, name_start_idx + name.len());
+
+		accepted.then_some(name)
+	}
+
+	/// Returns whether openings are also found by scanning for any well-formed name.
+	fn discovers_names(&self) -> bool {
+		self.wildcard || !self.name_matchers.is_empty()
+	}
+
+	/// Returns whether a discovered name is accepted by wildcard mode or by a non-exact matcher.
+	fn accepts_discovered_name(&self, name: &str) -> bool {
+		self.wildcard
+			|| self
+				.name_matchers
+				.iter()
+				.any(|matcher| matcher.matches_with_case(name, self.ignore_case))
+	}
+
+	/// Returns whether two tag names are equal, regardless of ASCII case when names are case-insensitive.
+	fn names_eq(&self, name: &str, other_name: &str) -> bool {
+		self.names_eq_bytes(name.as_bytes(), other_name.as_bytes())
+	}
+
+	fn names_eq_bytes(&self, name: &[u8], other_name: &[u8]) -> bool {
+		if self.ignore_case {
+			name.eq_ignore_ascii_case(other_name)
+		} else {
+			name == other_name
+		}
+	}
+
+	/// Finds the start of a trailing discovered opening candidate that more input could still make valid.
+	fn find_discovered_tail(&self, input: &str, from_idx: usize) -> Option<usize> {
+		if !self.discovers_names() {
+			return None;
+		}
+
+		let start_idx = from_idx + input.get(from_idx..)?.rfind(self.fence.open_delim)?;
+
+		self.is_discovered_candidate_open(input, start_idx).then_some(start_idx)
+	}
+
+	/// Returns whether the name after the opening delimiter at `start_idx` runs to the end of the input,
+	/// so more input could still make it a valid discovered opening.
+	fn is_discovered_candidate_open(&self, input: &str, start_idx: usize) -> bool {
+		let name_start_idx = start_idx + self.fence.open_delim.len();
+		let Some(remaining_input) = input.get(name_start_idx..) else {
+			return false;
+		};
+		let after_name = &remaining_input[well_formed_name_len(remaining_input)..];
+
+		after_name.is_empty()
+			|| self
+				.close_delims
+				.iter()
+				.chain([&self.fence.closing_tag_prefix])
+				.any(|token| token.len() > after_name.len() && token.starts_with(after_name))
+	}
+
+	/// Returns whether the character after a tag name is a closing delimiter, the closing prefix, or whitespace.
+	fn is_valid_after_name(&self, input: &str, after_name_idx: usize) -> bool {
+		let remaining_after_name = &input[after_name_idx..];
+
+		self.close_delims
+			.iter()
+			.any(|close_delim| remaining_after_name.starts_with(close_delim))
+			|| remaining_after_name.starts_with(self.fence.closing_tag_prefix)
+			|| matches!(
+				input.as_bytes().get(after_name_idx),
+				Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r')
+			)
+	}
+
+	/// Finds the close delimiter of the opening tag starting at `start_idx`.
+	pub fn parse_open_tag(&self, input: &str, start_idx: usize, tag_info: &TagPattern) -> Option<OpenTag> {
+		let (close_offset, close_delim_len) =
+			find_next_match(&input[start_idx..], tag_info.close_delims.iter().copied())?;
+		let close_start_idx = start_idx + close_offset;
+		let opening_tag_body = &input[start_idx + tag_info.start_tag_prefix.len()..close_start_idx];
+
+		Some(OpenTag {
+			close_start_idx,
+			end_idx: close_start_idx + close_delim_len - 1,
+			self_closing: opening_tag_body.ends_with(&tag_info.self_closing_suffix),
+		})
+	}
+
+	/// Returns whether a valid opening tag starts before the close delimiter of the opening tag at `start_idx`,
+	/// so the close delimiter belongs to a later element (e.g., a typo like `<FILE path="a.rs"` before `<DATA>`).
+	///
+	/// Openings inside quoted attribute values are ignored, and a quote left open runs to the close delimiter.
+	pub fn is_interrupted_open_tag(
+		&self,
+		input: &str,
+		start_idx: usize,
+		tag_info: &TagPattern,
+		open_tag: &OpenTag,
+	) -> bool {
+		let open_delim = self.fence.open_delim;
+		let attrs_start_idx = start_idx + tag_info.start_tag_prefix.len();
+		let region = &input[..open_tag.close_start_idx];
+		let mut current_pos = attrs_start_idx;
+
+		while let Some((offset, _)) = find_next_match(&region[current_pos..], [open_delim, "\"", "'"]) {
+			let idx = current_pos + offset;
+			let quote = region[idx..].chars().next().filter(|c| matches!(c, '"' | '\''));
+			match quote {
+				// A quote starts a value only right after `=`, ignoring whitespace.
+				Some(quote) if region[attrs_start_idx..idx].trim_end().ends_with('=') => {
+					let Some(quote_len) = region[idx + 1..].find(quote) else {
+						return false;
+					};
+					current_pos = idx + 1 + quote_len + 1;
+				}
+				Some(_) => current_pos = idx + 1,
+				None if self.opening_at(input, idx, None).is_some() => return true,
+				None => current_pos = input.ceil_char_boundary(idx + 1),
+			}
+		}
+
+		false
+	}
+
+	/// Starts the search for the end of the content of a non-self-closing element, right after its opening tag.
+	///
+	/// The search looks for a terminator line when the opening tag has a non-empty terminator attribute.
+	pub fn elem_end_search(
+		&self,
+		input: &str,
+		start_idx: usize,
+		tag_info: &TagPattern,
+		open_tag: &OpenTag,
+	) -> ElemEndSearch {
+		let terminator = self.terminator_attr.and_then(|terminator_attr| {
+			let attrs_section = attrs_section(input, start_idx, tag_info, open_tag);
+			let attrs = parse_attrs_ref(Some(attrs_section), self.attr_decoding)?;
+			let terminator = attrs.get(terminator_attr)?.trim();
+			(!terminator.is_empty()).then(|| terminator.to_string())
+		});
+
+		ElemEndSearch {
+			search_idx: open_tag.end_idx + 1,
+			depth: 0,
+			terminator,
+			raw_open_idx: None,
+			line_start_idx: None,
+		}
+	}
+
+	/// Finds the closing tags of configured or accepted discovered names within `input[from_idx..to_idx]`.
+	///
+	/// Returns the start index, the name as spelled in the input, and the length of each closing tag.
+	pub fn find_closing_tags<'i>(
+		&self,
+		input: &'i str,
+		from_idx: usize,
+		to_idx: usize,
+	) -> Vec<(usize, &'i str, usize)> {
+		let open_delim = self.fence.open_delim;
+		let closing_tag_prefix = self.fence.closing_tag_prefix;
+		let region = &input[..to_idx];
+		let mut closing_tags = Vec::new();
+		let mut current_pos = from_idx;
+
+		while let Some(offset) = region.get(current_pos..).and_then(|rest| rest.find(open_delim)) {
+			let start_idx = current_pos + offset;
+			current_pos = region.ceil_char_boundary(start_idx + 1);
+
+			let prefix_end_idx = start_idx + open_delim.len() + closing_tag_prefix.len();
+			if !region[start_idx + open_delim.len()..].starts_with(closing_tag_prefix) {
+				continue;
+			}
+
+			let name = match self.match_configured_name(region, prefix_end_idx, None) {
+				Some(tag_info) => &region[prefix_end_idx..prefix_end_idx + tag_info.name.len()],
+				None if self.discovers_names() => {
+					let name =
+						&region[prefix_end_idx..prefix_end_idx + well_formed_name_len(&region[prefix_end_idx..])];
+					if name.is_empty() || !self.accepts_discovered_name(name) {
+						continue;
+					}
+					name
+				}
+				None => continue,
+			};
+
+			let name_end_idx = prefix_end_idx + name.len();
+			let close_delim_len = self
+				.close_delims
+				.iter()
+				.filter(|close_delim| region[name_end_idx..].starts_with(**close_delim))
+				.map(|close_delim| close_delim.len())
+				.max();
+			if let Some(close_delim_len) = close_delim_len {
+				let len = name_end_idx + close_delim_len - start_idx;
+				closing_tags.push((start_idx, &input[name_end_idx - name.len()..name_end_idx], len));
+				current_pos = start_idx + len;
+			}
+		}
+
+		closing_tags
+	}
+
+	/// Returns the length of the closing tag of `tag_info` starting at `idx`, preferring the longest one.
+	fn close_tag_len_at(&self, input: &str, idx: usize, tag_info: &TagPattern) -> Option<usize> {
+		let remaining_input = &input.as_bytes()[idx..];
+
+		tag_info
+			.end_tags
+			.iter()
+			.filter(|end_tag| {
+				remaining_input
+					.get(..end_tag.len())
+					.is_some_and(|candidate| self.names_eq_bytes(candidate, end_tag.as_bytes()))
+			})
+			.map(String::len)
+			.max()
+	}
+
+	/// Returns whether a valid opening tag of `tag_info` starts at `idx`.
+	fn is_same_name_opening_at(&self, input: &str, idx: usize, tag_info: &TagPattern) -> bool {
+		let prefix_len = tag_info.start_tag_prefix.len();
+
+		input.as_bytes()[idx..]
+			.get(..prefix_len)
+			.is_some_and(|candidate| self.names_eq_bytes(candidate, tag_info.start_tag_prefix.as_bytes()))
+			&& self.is_valid_after_name(input, idx + prefix_len)
+	}
+
+	/// Returns the name under which a `tag_info` element without a closing tag can be remembered, if any.
+	///
+	/// Outside nested mode, raw sections, and terminator lines, once a name has no closing tag after an index,
+	/// no later element with that name has one, so the complete input need not be searched again.
+	pub fn unclosed_name_key<'t>(&self, tag_info: &'t TagPattern, search: &ElemEndSearch) -> Option<&'t str> {
+		(!self.nested && self.raw_section.is_none() && search.terminator.is_none()).then_some(tag_info.name.as_str())
+	}
+
+	/// Finds where the content of a `tag_info` element ends, continuing the given `search`.
+	///
+	/// The search is a single forward pass over the opening delimiters, where the first closing tag,
+	/// nested opening, or auto-close opening decides. In nested mode, same-name openings increase the
+	/// depth and only the closing tag at depth zero ends the element. With auto-close, only openings
+	/// found at depth zero synthesize a close. The text of raw sections is skipped, while a raw
+	/// section without its close delimiter in the complete input is plain text.
+	pub fn find_element_end(
+		&self,
+		input: &str,
+		tag_info: &TagPattern,
+		search: &mut ElemEndSearch,
+		complete: bool,
+	) -> ElemEnd {
+		if search.terminator.is_some() {
+			return self.find_terminator_line(input, search, complete);
+		}
+
+		let exclude_name = self.nested.then_some(tag_info.name.as_str());
+		// A name found in wildcard mode can be longer than any configured name.
+		let lookahead = self.lookahead.max(tag_info.lookahead());
+		// In the complete input, once a nested opening has no close delimiter, no later one has either.
+		let mut nested_openings_closed = true;
+		// Likewise, once a raw section has no close delimiter, no later one has either.
+		let mut raw_sections_closed = true;
+		// The next raw section open delimiter, or the input length when there is none, searched again only once passed.
+		let mut next_raw_open_idx: Option<usize> = None;
+
+		loop {
+			// -- Raw section, skipped through its close delimiter
+			if let Some(raw_section) = self.raw_section
+				&& let Some(raw_open_idx) = search.raw_open_idx
+			{
+				match input[search.search_idx..].find(raw_section.close) {
+					Some(offset) => search.search_idx += offset + raw_section.close.len(),
+					// Resume where the close delimiter may start, so the body received so far is not searched again.
+					None if !complete => {
+						let body_idx = raw_open_idx + raw_section.open.len();
+						let close_search_idx = input.len().saturating_sub(raw_section.close.len() - 1);
+						search.search_idx = input.floor_char_boundary(close_search_idx).max(body_idx);
+						return ElemEnd::Pending;
+					}
+					// Without its close delimiter, the raw section is plain text.
+					None => {
+						search.search_idx = raw_open_idx;
+						raw_sections_closed = false;
+					}
+				}
+				search.raw_open_idx = None;
+				continue;
+			}
+
+			let search_idx = search.search_idx;
+			let raw_open_idx = match self.raw_section {
+				Some(raw_section) if raw_sections_closed => {
+					if next_raw_open_idx.is_none_or(|raw_open_idx| raw_open_idx < search_idx) {
+						let raw_open_idx = input[search_idx..]
+							.find(raw_section.open)
+							.map_or(input.len(), |offset| search_idx + offset);
+						next_raw_open_idx = Some(raw_open_idx);
+					}
+					next_raw_open_idx.filter(|&raw_open_idx| raw_open_idx < input.len())
+				}
+				_ => None,
+			};
+			let open_idx = input[search_idx..]
+				.find(self.fence.open_delim)
+				.map(|offset| search_idx + offset);
+			let Some(event_idx) = open_idx.into_iter().chain(raw_open_idx).min() else {
+				return match (complete, self.auto_close) {
+					(true, true) => ElemEnd::Eof,
+					(true, false) => ElemEnd::Unclosed,
+					(false, _) => {
+						search.search_idx = self.resume_idx_within(input, search_idx, lookahead);
+						ElemEnd::Pending
+					}
+				};
+			};
+
+			// -- Wait for the bytes that decide what starts at this delimiter
+			let undecided = !is_decided_within(input, event_idx, complete, lookahead)
+				|| (!complete && self.discovers_names() && self.is_discovered_candidate_open(input, event_idx));
+			if undecided {
+				search.search_idx = self.resume_idx_within(input, search_idx, lookahead).min(event_idx);
+				return ElemEnd::Pending;
+			}
+
+			// -- Raw section start
+			if let Some(raw_section) = self.raw_section
+				&& raw_open_idx == Some(event_idx)
+			{
+				search.raw_open_idx = Some(event_idx);
+				search.search_idx = event_idx + raw_section.open.len();
+				continue;
+			}
+
+			// -- Auto-close before a subsequent opening at depth zero
+			if self.auto_close && search.depth == 0 && self.opening_at(input, event_idx, exclude_name).is_some() {
+				return ElemEnd::Opening { start_idx: event_idx };
+			}
+
+			// -- Same-name opening (self-closing ones do not change the depth)
+			if self.nested && nested_openings_closed && self.is_same_name_opening_at(input, event_idx, tag_info) {
+				match self.parse_open_tag(input, event_idx, tag_info) {
+					Some(open_tag) => {
+						if !open_tag.self_closing {
+							search.depth += 1;
+						}
+						search.search_idx = open_tag.end_idx + 1;
+						continue;
+					}
+					None if complete => nested_openings_closed = false,
+					None => {
+						search.search_idx = self.resume_idx_within(input, search_idx, lookahead).min(event_idx);
+						return ElemEnd::Pending;
+					}
+				}
+			}
+
+			// -- Closing tag
+			if let Some(len) = self.close_tag_len_at(input, event_idx, tag_info) {
+				if search.depth == 0 {
+					return ElemEnd::Close {
+						start_idx: event_idx,
+						len,
+					};
+				}
+				search.depth -= 1;
+				search.search_idx = event_idx + len;
+				continue;
+			}
+
+			search.search_idx = input.ceil_char_boundary(event_idx + 1);
+		}
+	}
+
+	/// Finds the first line, after the line of the opening tag, consisting of the terminator of `search`,
+	/// ignoring surrounding whitespace. Closing tags, openings, and raw sections do not apply.
+	///
+	/// The terminator line is returned as the closing boundary, without its line break.
+	fn find_terminator_line(&self, input: &str, search: &mut ElemEndSearch, complete: bool) -> ElemEnd {
+		let terminator = search.terminator.as_deref().unwrap_or_default();
+
+		loop {
+			let line_end_idx = match input[search.search_idx..].find('\n') {
+				Some(offset) => search.search_idx + offset,
+				None if complete => input.len(),
+				// The line may still grow, so only the bytes received after it are searched next time.
+				None => {
+					search.search_idx = input.len();
+					return ElemEnd::Pending;
+				}
+			};
+
+			// The line of the opening tag is never a terminator line.
+			if let Some(line_start_idx) = search.line_start_idx
+				&& input[line_start_idx..line_end_idx].trim() == terminator
+			{
+				return ElemEnd::Close {
+					start_idx: line_start_idx,
+					len: line_end_idx - line_start_idx,
+				};
+			}
+			if line_end_idx == input.len() {
+				return if self.auto_close {
+					ElemEnd::Eof
+				} else {
+					ElemEnd::Unclosed
+				};
+			}
+			search.line_start_idx = Some(line_end_idx + 1);
+			search.search_idx = line_end_idx + 1;
+		}
+	}
+
+	/// Builds the element for a parsed opening tag and its resolved end, or `None` for an unresolved end.
+	///
+	/// A self-closing opening tag ignores `end`.
+	pub fn build_elem_ref<'a>(
+		&self,
+		input: &'a str,
+		start_idx: usize,
+		tag_info: &TagPattern,
+		open_tag: &OpenTag,
+		end: &ElemEnd,
+	) -> Option<TagElemRef<'a>> {
+		let after_prefix_idx = start_idx + tag_info.start_tag_prefix.len();
+		let tag_name = &input[after_prefix_idx - tag_info.name.len()..after_prefix_idx];
+
+		// --- Extract Parameters (exclude self-closing suffix) ---
+		let attrs_section = attrs_section(input, start_idx, tag_info, open_tag);
+		let attrs = parse_attrs_ref(Some(attrs_section), self.attr_decoding);
+		let mut spans = TagElemSpans {
+			open_tag: TagSpan::new(start_idx, open_tag.end_idx + 1),
+			name: TagSpan::new(after_prefix_idx - tag_name.len(), after_prefix_idx),
+			attrs: parse_attr_spans(attrs_section, after_prefix_idx, self.attr_decoding),
+			content: TagSpan::new(open_tag.end_idx + 1, open_tag.end_idx + 1),
+			close_tag: None,
+		};
+
+		if open_tag.self_closing {
+			// Self-closing: no content, no closing tag
+			return Some(TagElemRef {
+				tag_name,
+				attrs,
+				content: "",
+				auto_closed: false,
+				start_idx,
+				end_idx: open_tag.end_idx,
+				spans,
+			});
+		}
+
+		// The end index is the index of the last character of the closing tag (or of the content when auto-closed)
+		let content_start_idx = open_tag.end_idx + 1;
+		let (content_end_idx, end_idx, auto_closed) = match *end {
+			ElemEnd::Close { start_idx, len } => (start_idx, start_idx + len - 1, false),
+			ElemEnd::Opening { start_idx } => (start_idx, start_idx - 1, true),
+			ElemEnd::Eof => (input.len(), input.len() - 1, true),
+			ElemEnd::Unclosed | ElemEnd::Pending => return None,
+		};
+
+		spans.content = TagSpan::new(content_start_idx, content_end_idx);
+		if !auto_closed {
+			spans.close_tag = Some(TagSpan::new(content_end_idx, end_idx + 1));
+		}
+
+		Some(TagElemRef {
+			tag_name,
+			attrs,
+			content: &input[content_start_idx..content_end_idx],
+			auto_closed,
+			start_idx,
+			end_idx,
+			spans,
+		})
+	}
+}
+
+// region:    --- Support
+
+/// Returns the attributes of the opening tag as written, excluding the self-closing suffix.
+fn attrs_section<'i>(input: &'i str, start_idx: usize, tag_info: &TagPattern, open_tag: &OpenTag) -> &'i str {
+	let opening_tag_body = &input[start_idx + tag_info.start_tag_prefix.len()..open_tag.close_start_idx];
+	if open_tag.self_closing {
+		opening_tag_body[..opening_tag_body.len() - tag_info.self_closing_suffix.len()].trim_end()
+	} else {
+		opening_tag_body
+	}
+}
+
+/// Returns whether a match starting at `idx` is decided, given the bytes needed to decide it.
+fn is_decided_within(input: &str, idx: usize, complete: bool, lookahead: usize) -> bool {
+	complete || idx + lookahead <= input.len()
+}
+
+/// Returns the length of the well-formed tag name at the start of `input`, or zero.
+///
+/// A well-formed name starts with an ASCII letter or `_`, followed by ASCII alphanumerics, `_`, `-`, `.`, or `:`.
+fn well_formed_name_len(input: &str) -> usize {
+	let bytes = input.as_bytes();
+	if !bytes.first().is_some_and(|b| b.is_ascii_alphabetic() || *b == b'_') {
+		return 0;
+	}
+
+	bytes
+		.iter()
+		.position(|b| !(b.is_ascii_alphanumeric() || matches!(b, b'_' | b'-' | b'.' | b':')))
+		.unwrap_or(bytes.len())
+}
+
+/// Finds the leftmost match among `patterns`, preferring the longest pattern at the same position.
+pub(crate) fn find_next_match<'a>(input: &str, patterns: impl IntoIterator<Item = &'a str>) -> Option<(usize, usize)> {
+	let mut selected = None;
+
+	for pattern in patterns {
+		if let Some(start_idx) = input.find(pattern) {
+			selected = match selected {
+				None => Some((start_idx, pattern.len())),
+				Some((existing_idx, existing_len)) => {
+					if start_idx < existing_idx || (start_idx == existing_idx && pattern.len() > existing_len) {
+						Some((start_idx, pattern.len()))
+					} else {
+						Some((existing_idx, existing_len))
+					}
+				}
+			};
+		}
+	}
+
+	selected
+}
+
+// endregion: --- Support
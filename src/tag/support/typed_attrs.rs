@@ -0,0 +1,59 @@
+use crate::{Error, Result};
+use std::fmt;
+use std::str::FromStr;
+
+/// Converts the looked up attribute value of an element, where `None` is an absent key and `Some(None)` a flag.
+pub fn parse_attr<T>(tag_name: &str, key: &str, lookup: Option<Option<&str>>) -> Result<Option<T>>
+where
+	T: FromStr,
+	T::Err: fmt::Display,
+{
+	let Some(value) = lookup else {
+		return Ok(None);
+	};
+	let value = value.ok_or_else(|| invalid_attr(tag_name, key, "", "attribute has no value"))?;
+
+	value
+		.parse()
+		.map(Some)
+		.map_err(|err: T::Err| invalid_attr(tag_name, key, value, err))
+}
+
+/// Converts the looked up attribute value of an element to a bool, accepting `true`/`false`, `yes`/`no`,
+/// and `1`/`0` regardless of ASCII case. A flag without value is `true`.
+pub fn parse_attr_bool(tag_name: &str, key: &str, lookup: Option<Option<&str>>) -> Result<Option<bool>> {
+	let Some(value) = lookup else {
+		return Ok(None);
+	};
+	let Some(value) = value else {
+		return Ok(Some(true));
+	};
+
+	let value_lower = value.trim().to_ascii_lowercase();
+	match value_lower.as_str() {
+		"true" | "yes" | "1" => Ok(Some(true)),
+		"false" | "no" | "0" => Ok(Some(false)),
+		_ => Err(invalid_attr(
+			tag_name,
+			key,
+			value,
+			"expected true/false, yes/no, or 1/0",
+		)),
+	}
+}
+
+pub fn missing_attr(tag_name: &str, key: &str) -> Error {
+	Error::MissingAttr {
+		tag_name: tag_name.to_string(),
+		key: key.to_string(),
+	}
+}
+
+pub fn invalid_attr(tag_name: &str, key: &str, value: &str, cause: impl fmt::Display) -> Error {
+	Error::InvalidAttr {
+		tag_name: tag_name.to_string(),
+		key: key.to_string(),
+		value: value.to_string(),
+		cause: cause.to_string(),
+	}
+}
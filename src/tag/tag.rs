@@ -1,51 +1,195 @@
 //! Definition of the extracted element structure.
 
-use serde::Serialize;
-use std::collections::HashMap;
+use crate::Result;
+use crate::tag::support::{missing_attr, parse_attr, parse_attr_bool, write_elem_markup};
+use crate::tag::tag_deserializer::{TagElemDeserializer, deserialize_elem};
+use crate::tag::{AttrDecoding, Attrs, Part, Parts, TagElemRef, TagFence, TagOptions, TagSpan};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
 
 // region:    --- TagElem
 
 /// Represents a block defined by start and end tags, like `<TAG>content</TAG>`.
-#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+///
+/// Equality and serialization ignore `span`, so the same element extracted from different inputs compares equal.
+#[derive(Debug, Serialize, Clone, Default)]
 pub struct TagElem {
 	pub tag: String, // might want to set this a Arc<str>
 
-	pub attrs: Option<HashMap<String, String>>,
+	pub attrs: Option<Attrs>,
 
 	pub content: String,
 
 	pub auto_closed: bool,
+
+	/// The byte span of the element in the input it was extracted from.
+	///
+	/// It is empty at `0` for an element created with `TagElem::new`.
+	#[serde(skip)]
+	pub span: TagSpan,
 }
 
 /// Constructors
 impl TagElem {
 	/// Creates a new `TagElem` with the specified name, optional attributes, and content.
-	pub fn new(name: impl Into<String>, attrs: Option<HashMap<String, String>>, content: impl Into<String>) -> Self {
+	pub fn new(name: impl Into<String>, attrs: Option<Attrs>, content: impl Into<String>) -> Self {
 		TagElem {
 			tag: name.into(),
 			attrs,
 			content: content.into(),
 			auto_closed: false,
+			span: TagSpan::default(),
 		}
 	}
 }
 
-// endregion: --- Froms
+/// Accessors
+impl TagElem {
+	/// Returns the tag name in ASCII uppercase, so names matched regardless of case compare equal.
+	///
+	/// `tag` keeps the spelling used in the input (e.g., `file` for `<file>`).
+	pub fn normalized_name(&self) -> Cow<'_, str> {
+		if self.tag.bytes().any(|b| b.is_ascii_lowercase()) {
+			Cow::Owned(self.tag.to_ascii_uppercase())
+		} else {
+			Cow::Borrowed(&self.tag)
+		}
+	}
+}
+
+/// Typed Attributes
+impl TagElem {
+	/// Returns the value of the attribute converted with `FromStr`, or `None` when the key is absent.
+	///
+	/// Fails with `Error::InvalidAttr` when the value does not convert, or when the attribute is a flag without value.
+	pub fn attr<T>(&self, key: &str) -> Result<Option<T>>
+	where
+		T: FromStr,
+		T::Err: fmt::Display,
+	{
+		parse_attr(&self.tag, key, self.attrs.as_ref().and_then(|attrs| attrs.lookup(key)))
+	}
+
+	/// Returns the value of the attribute converted with `FromStr`, failing with `Error::MissingAttr` when the key is absent.
+	pub fn required_attr<T>(&self, key: &str) -> Result<T>
+	where
+		T: FromStr,
+		T::Err: fmt::Display,
+	{
+		self.attr(key)?.ok_or_else(|| missing_attr(&self.tag, key))
+	}
+
+	/// Returns the value of the attribute converted with `FromStr`, or `default` when the key is absent.
+	pub fn attr_or<T>(&self, key: &str, default: T) -> Result<T>
+	where
+		T: FromStr,
+		T::Err: fmt::Display,
+	{
+		Ok(self.attr(key)?.unwrap_or(default))
+	}
+
+	/// Returns the value of the attribute as a bool, or `None` when the key is absent.
+	///
+	/// Accepts `true`/`false`, `yes`/`no`, and `1`/`0` regardless of ASCII case, and a flag without value
+	/// (e.g., `<FILE overwrite>`) is `true`. Fails with `Error::InvalidAttr` for any other value.
+	pub fn attr_bool(&self, key: &str) -> Result<Option<bool>> {
+		parse_attr_bool(&self.tag, key, self.attrs.as_ref().and_then(|attrs| attrs.lookup(key)))
+	}
+}
+
+/// Rendering
+impl TagElem {
+	/// Returns the element as markup under the fence, which `extract` with the same fence reads back.
+	///
+	/// The tag is self-closing when the content is empty (e.g., `<DELETE path="a.rs"/>`), and paired otherwise.
+	/// Attribute values are double-quoted, or single-quoted when they contain a double quote, and flags have no value.
+	/// Use `Parts::render` to escape values for an `AttrDecoding`.
+	///
+	/// Fails with `Error::InvalidAttr` for a value that cannot be written without escapes, which is a value
+	/// containing both quote characters, or a delimiter of the fence (e.g., `a > b` under `FENCE_XML`).
+	pub fn to_markup(&self, fence: &TagFence) -> Result<String> {
+		let mut markup = String::new();
+		write_elem_markup(&mut markup, self, fence, AttrDecoding::Raw)?;
+		Ok(markup)
+	}
+
+	/// Returns the element as markup under the first of the `fences` that extracts back to the same element,
+	/// with that fence.
+	///
+	/// A fence is skipped when the content contains the closing tag of the element under it (e.g., a file
+	/// containing `</FILE>`), or when extracting the markup does not return the same tag name, attributes,
+	/// and content. Fails with `Error::RenderCollision` when no fence qualifies.
+	pub fn to_markup_checked(&self, fences: &[TagFence]) -> Result<(String, TagFence)> {
+		let parts = Parts {
+			parts: vec![Part::TagElem(self.clone())],
+		};
+		parts.render_checked(&TagOptions::default(), fences)
+	}
+}
+
+/// Deserialization
+impl TagElem {
+	/// Deserializes the element into a serde type, such as a struct with a field per attribute,
+	/// and the content under the `$content` key (see `CONTENT_KEY`).
+	///
+	/// An enum is matched by tag name, so `FILE { path: String }` takes `<FILE path="a.rs">`.
+	/// Attribute values convert to scalar fields like the typed accessors (e.g., `attr_bool` for a `bool`),
+	/// and the last value wins for a repeated key.
+	///
+	/// Fails with `Error::InvalidAttr` for a value that does not convert, and `Error::InvalidElem`
+	/// for other mismatches, such as a missing field or an unknown tag name.
+	pub fn deserialize_as<'de, T: Deserialize<'de>>(&'de self) -> Result<T> {
+		deserialize_elem(TagElemDeserializer::from_elem(self))
+	}
+}
+
+// endregion: --- TagElem
+
+// region:    --- Comparison
+
+// Manual impl, since the span locates the element in one input rather than being part of its value.
+impl PartialEq for TagElem {
+	fn eq(&self, other: &Self) -> bool {
+		self.tag == other.tag
+			&& self.attrs == other.attrs
+			&& self.content == other.content
+			&& self.auto_closed == other.auto_closed
+	}
+}
+
+// endregion: --- Comparison
 
 // region:    --- Froms
 
 /// Conversions
-impl From<crate::tag::TagElemRef<'_>> for TagElem {
-	fn from(tag_ref: crate::tag::TagElemRef<'_>) -> Self {
+impl TagElem {
+	/// Converts `tag_ref` like `From`, removing the delimiters of the raw sections of `fence` from the content,
+	/// as the owned extraction APIs do.
+	pub(crate) fn from_ref_in_fence(tag_ref: TagElemRef<'_>, fence: &TagFence) -> Self {
+		let content = fence.unwrap_raw_sections(tag_ref.content).into_owned();
+		Self::from_ref_with_content(tag_ref, content)
+	}
+
+	fn from_ref_with_content(tag_ref: TagElemRef<'_>, content: String) -> Self {
+		let span = tag_ref.span();
 		TagElem {
 			tag: tag_ref.tag_name.to_string(),
-			attrs: tag_ref
-				.attrs
-				.map(|attrs| attrs.into_iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()),
-			content: tag_ref.content.to_string(),
+			attrs: tag_ref.attrs.map(Attrs::from),
+			content,
 			auto_closed: tag_ref.auto_closed,
+			span,
 		}
 	}
 }
 
-// endregion: --- TagElem
+/// Keeps the content as written, including raw sections (see `TagFence::unwrap_raw_sections`).
+impl From<TagElemRef<'_>> for TagElem {
+	fn from(tag_ref: TagElemRef<'_>) -> Self {
+		let content = tag_ref.content.to_string();
+		Self::from_ref_with_content(tag_ref, content)
+	}
+}
+
+// endregion: --- Froms
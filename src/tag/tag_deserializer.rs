@@ -0,0 +1,357 @@
+//! Serde deserialization of extracted elements into user types.
+//!
+//! An element deserializes as a map of its attributes plus its content under the `$content` key,
+//! or, for an enum, as the variant named after its tag name.
+
+use crate::Error;
+use crate::tag::support::{invalid_attr, parse_attr, parse_attr_bool};
+use crate::tag::{TagElem, TagElemRef};
+use serde::de::value::{BorrowedStrDeserializer, StringDeserializer};
+use serde::de::{self, Deserialize, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use std::borrow::Cow;
+use std::fmt;
+
+/// The key under which the element content is deserialized, e.g., `#[serde(rename = "$content")]`.
+pub const CONTENT_KEY: &str = "$content";
+
+/// Deserializes a `T` from the element, attaching the tag name to the serde errors.
+pub(crate) fn deserialize_elem<'de, T: Deserialize<'de>>(deserializer: TagElemDeserializer<'de>) -> crate::Result<T> {
+	let tag_name = deserializer.tag_name;
+	T::deserialize(deserializer).map_err(|err| match err {
+		DeError::Tag(err) => err,
+		DeError::Custom(cause) => Error::InvalidElem {
+			tag_name: tag_name.to_string(),
+			cause,
+		},
+	})
+}
+
+// region:    --- TagElemDeserializer
+
+/// A serde `Deserializer` over the attributes, content, and tag name of an element.
+pub(crate) struct TagElemDeserializer<'de> {
+	tag_name: &'de str,
+	/// The attributes in source order, keeping only the last of duplicate keys.
+	attrs: Vec<(&'de str, Option<Cow<'de, str>>)>,
+	content: &'de str,
+}
+
+/// Constructors
+impl<'de> TagElemDeserializer<'de> {
+	pub(crate) fn from_elem(elem: &'de TagElem) -> Self {
+		let attrs = elem
+			.attrs
+			.iter()
+			.flat_map(|attrs| attrs.iter())
+			.map(|(key, value)| (key, value.map(Cow::Borrowed)))
+			.collect();
+
+		TagElemDeserializer {
+			tag_name: &elem.tag,
+			attrs: last_per_key(attrs),
+			content: &elem.content,
+		}
+	}
+
+	pub(crate) fn from_elem_ref(elem: &TagElemRef<'de>) -> Self {
+		let attrs = elem.attrs.iter().flat_map(|attrs| attrs.entries().iter().cloned()).collect();
+
+		TagElemDeserializer {
+			tag_name: elem.tag_name,
+			attrs: last_per_key(attrs),
+			content: elem.content,
+		}
+	}
+}
+
+impl<'de> de::Deserializer<'de> for TagElemDeserializer<'de> {
+	type Error = DeError;
+
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+		let entries = self
+			.attrs
+			.into_iter()
+			.chain(std::iter::once((CONTENT_KEY, Some(Cow::Borrowed(self.content)))));
+
+		visitor.visit_map(ElemMapAccess {
+			tag_name: self.tag_name,
+			entries,
+			pending: None,
+		})
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, DeError> {
+		visitor.visit_enum(self)
+	}
+
+	fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+		visitor.visit_unit()
+	}
+
+	forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf option unit unit_struct newtype_struct seq tuple
+		tuple_struct map struct identifier
+	}
+}
+
+/// Dispatches on the tag name as the enum variant.
+impl<'de> de::EnumAccess<'de> for TagElemDeserializer<'de> {
+	type Error = DeError;
+	type Variant = Self;
+
+	fn variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<(S::Value, Self), DeError> {
+		let variant = seed.deserialize(BorrowedStrDeserializer::<DeError>::new(self.tag_name))?;
+		Ok((variant, self))
+	}
+}
+
+impl<'de> de::VariantAccess<'de> for TagElemDeserializer<'de> {
+	type Error = DeError;
+
+	fn unit_variant(self) -> Result<(), DeError> {
+		Ok(())
+	}
+
+	fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value, DeError> {
+		seed.deserialize(self)
+	}
+
+	fn tuple_variant<V: Visitor<'de>>(self, _len: usize, _visitor: V) -> Result<V::Value, DeError> {
+		Err(de::Error::invalid_type(
+			de::Unexpected::TupleVariant,
+			&"a unit, newtype, or struct variant",
+		))
+	}
+
+	fn struct_variant<V: Visitor<'de>>(
+		self,
+		_fields: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, DeError> {
+		de::Deserializer::deserialize_any(self, visitor)
+	}
+}
+
+// endregion: --- TagElemDeserializer
+
+// region:    --- ElemMapAccess
+
+struct ElemMapAccess<'de, I> {
+	tag_name: &'de str,
+	entries: I,
+	pending: Option<(&'de str, Option<Cow<'de, str>>)>,
+}
+
+impl<'de, I> de::MapAccess<'de> for ElemMapAccess<'de, I>
+where
+	I: Iterator<Item = (&'de str, Option<Cow<'de, str>>)>,
+{
+	type Error = DeError;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, DeError> {
+		let Some((key, value)) = self.entries.next() else {
+			return Ok(None);
+		};
+		self.pending = Some((key, value));
+
+		seed.deserialize(BorrowedStrDeserializer::<DeError>::new(key)).map(Some)
+	}
+
+	fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+		let (key, value) = self
+			.pending
+			.take()
+			.ok_or_else(|| DeError::Custom("value requested before key".to_string()))?;
+
+		seed.deserialize(AttrValueDeserializer {
+			tag_name: self.tag_name,
+			key,
+			value,
+		})
+	}
+}
+
+// endregion: --- ElemMapAccess
+
+// region:    --- AttrValueDeserializer
+
+/// A serde `Deserializer` over an attribute value, or the content, converting it with `FromStr`
+/// for the requested scalar type.
+struct AttrValueDeserializer<'de> {
+	tag_name: &'de str,
+	key: &'de str,
+	/// `None` for a flag without value.
+	value: Option<Cow<'de, str>>,
+}
+
+impl<'de> AttrValueDeserializer<'de> {
+	fn parse<T>(&self) -> Result<T, DeError>
+	where
+		T: std::str::FromStr,
+		T::Err: fmt::Display,
+	{
+		let value = parse_attr(self.tag_name, self.key, Some(self.value.as_deref()))?;
+		value.ok_or_else(|| DeError::Custom("attribute value should be present".to_string()))
+	}
+
+	fn into_value(self) -> Result<Cow<'de, str>, DeError> {
+		self.value
+			.ok_or_else(|| DeError::Tag(invalid_attr(self.tag_name, self.key, "", "attribute has no value")))
+	}
+}
+
+macro_rules! deserialize_parsed {
+	($($method:ident => $visit:ident,)*) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+				visitor.$visit(self.parse()?)
+			}
+		)*
+	};
+}
+
+impl<'de> de::Deserializer<'de> for AttrValueDeserializer<'de> {
+	type Error = DeError;
+
+	/// Visits the value as a string, or a flag without value as `true`.
+	fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+		match self.value {
+			Some(Cow::Borrowed(value)) => visitor.visit_borrowed_str(value),
+			Some(Cow::Owned(value)) => visitor.visit_string(value),
+			None => visitor.visit_bool(true),
+		}
+	}
+
+	fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+		let value = parse_attr_bool(self.tag_name, self.key, Some(self.value.as_deref()))?;
+		visitor.visit_bool(value.unwrap_or(true))
+	}
+
+	deserialize_parsed! {
+		deserialize_i8 => visit_i8,
+		deserialize_i16 => visit_i16,
+		deserialize_i32 => visit_i32,
+		deserialize_i64 => visit_i64,
+		deserialize_i128 => visit_i128,
+		deserialize_u8 => visit_u8,
+		deserialize_u16 => visit_u16,
+		deserialize_u32 => visit_u32,
+		deserialize_u64 => visit_u64,
+		deserialize_u128 => visit_u128,
+		deserialize_f32 => visit_f32,
+		deserialize_f64 => visit_f64,
+		deserialize_char => visit_char,
+	}
+
+	fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+		match self.into_value()? {
+			Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+			Cow::Owned(value) => visitor.visit_string(value),
+		}
+	}
+
+	fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+		self.deserialize_str(visitor)
+	}
+
+	/// A present attribute is always `Some`, since absent keys are never visited.
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+		visitor.visit_some(self)
+	}
+
+	fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+		visitor.visit_unit()
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, DeError> {
+		visitor.visit_newtype_struct(self)
+	}
+
+	/// Matches the value as a unit variant name.
+	fn deserialize_enum<V: Visitor<'de>>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value, DeError> {
+		match self.into_value()? {
+			Cow::Borrowed(value) => visitor.visit_enum(BorrowedStrDeserializer::new(value)),
+			Cow::Owned(value) => visitor.visit_enum::<StringDeserializer<DeError>>(value.into_deserializer()),
+		}
+	}
+
+	forward_to_deserialize_any! {
+		bytes byte_buf unit_struct seq tuple tuple_struct map struct identifier ignored_any
+	}
+}
+
+// endregion: --- AttrValueDeserializer
+
+// region:    --- DeError
+
+/// The serde error of the element deserializers, converted to `crate::Error` by `deserialize_elem`.
+#[derive(Debug)]
+pub(crate) enum DeError {
+	/// An error already carrying the tag context (e.g., `Error::InvalidAttr`).
+	Tag(Error),
+	/// A serde error, such as a missing field or an unknown variant.
+	Custom(String),
+}
+
+impl From<Error> for DeError {
+	fn from(err: Error) -> Self {
+		DeError::Tag(err)
+	}
+}
+
+impl fmt::Display for DeError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			DeError::Tag(err) => write!(f, "{err}"),
+			DeError::Custom(cause) => write!(f, "{cause}"),
+		}
+	}
+}
+
+impl std::error::Error for DeError {}
+
+impl de::Error for DeError {
+	fn custom<T: fmt::Display>(msg: T) -> Self {
+		DeError::Custom(msg.to_string())
+	}
+}
+
+// endregion: --- DeError
+
+// region:    --- Support
+
+/// Keeps only the last attribute of each key, in source order, so a repeated key is not a duplicate field.
+fn last_per_key<'de>(mut attrs: Vec<(&'de str, Option<Cow<'de, str>>)>) -> Vec<(&'de str, Option<Cow<'de, str>>)> {
+	let mut idx = 0;
+	while idx < attrs.len() {
+		let key = attrs[idx].0;
+		if attrs[idx + 1..].iter().any(|(later_key, _)| *later_key == key) {
+			attrs.remove(idx);
+		} else {
+			idx += 1;
+		}
+	}
+	attrs
+}
+
+// endregion: --- Support
+
+// region:    --- Tests
+
+#[path = "tag_deserializer_tests.rs"]
+#[cfg(test)]
+mod tests;
+
+// endregion: --- Tests
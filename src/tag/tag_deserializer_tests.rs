@@ -0,0 +1,153 @@
+//! Tests for the serde deserialization of elements.
+
+use crate::Error;
+use crate::tag::{AttrDecoding, FENCE_XML, RAW_SECTION_CDATA, TagOptions, extract, extract_refs};
+use serde::Deserialize;
+
+type Result<T> = core::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct FileDirective {
+	path: String,
+	line: Option<u32>,
+	#[serde(default)]
+	overwrite: bool,
+	#[serde(rename = "$content")]
+	content: String,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "UPPERCASE")]
+enum Op {
+	File {
+		path: String,
+		#[serde(rename = "$content")]
+		content: String,
+	},
+	Delete {
+		path: String,
+	},
+	Clear,
+}
+
+#[test]
+fn test_tag_deserializer_elem_to_struct() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"<FILE path="a.rs" line=12 overwrite>fn main() {}</FILE>"#;
+
+	// -- Exec
+	let parts = extract(input, &["FILE"], None);
+	let file: FileDirective = parts.tag_elems()[0].deserialize_as()?;
+
+	// -- Check
+	assert_eq!(
+		file,
+		FileDirective {
+			path: "a.rs".to_string(),
+			line: Some(12),
+			overwrite: true,
+			content: "fn main() {}".to_string(),
+		}
+	);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_deserializer_elem_ref_borrows_and_decodes() -> Result<()> {
+	// -- Setup & Fixtures
+	#[derive(Deserialize)]
+	struct Commit<'a> {
+		author: &'a str,
+		msg: String,
+		#[serde(rename = "$content")]
+		body: &'a str,
+	}
+	let input = r#"<COMMIT author=me msg="fix \"parser\"" author=you>body</COMMIT>"#;
+	let options = TagOptions::default().with_attr_decoding(AttrDecoding::Backslash);
+
+	// -- Exec
+	let parts_ref = extract_refs(input, &["COMMIT"], options);
+	let commit: Commit = parts_ref.tag_elems()[0].deserialize_as()?;
+
+	// -- Check
+	assert_eq!(commit.author, "you");
+	assert_eq!(commit.msg, r#"fix "parser""#);
+	assert_eq!(commit.body, "body");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_deserializer_raw_section_content() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<FILE path=a.md><![CDATA[Reply with <FILE>...</FILE>.]]></FILE>";
+	let options = TagOptions::default().with_fence(FENCE_XML.with_raw_section(RAW_SECTION_CDATA));
+
+	// -- Exec
+	let parts = extract(input, &["FILE"], options);
+	let file: FileDirective = parts.tag_elems()[0].deserialize_as()?;
+
+	// -- Check
+	assert_eq!(file.content, "Reply with <FILE>...</FILE>.");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_deserializer_parts_to_enum() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"
+<FILE path="a.rs">fn a() {}</FILE>
+<DELETE path="b.rs"></DELETE>
+<CLEAR/>
+"#;
+
+	// -- Exec
+	let parts = extract(input, &["FILE", "DELETE", "CLEAR"], None);
+	let ops: Vec<Op> = parts.deserialize_elems()?;
+	let parts_ref = extract_refs(input, &["FILE", "DELETE", "CLEAR"], None);
+	let ops_ref: Vec<Op> = parts_ref.deserialize_elems()?;
+
+	// -- Check
+	let expected = vec![
+		Op::File {
+			path: "a.rs".to_string(),
+			content: "fn a() {}".to_string(),
+		},
+		Op::Delete {
+			path: "b.rs".to_string(),
+		},
+		Op::Clear,
+	];
+	assert_eq!(ops, expected);
+	assert_eq!(ops_ref, expected);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_deserializer_errors() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"<FILE line="twelve">body</FILE><DELETE/><MOVE path="a.rs"/>"#;
+
+	// -- Exec
+	let parts_ref = extract_refs(input, &["FILE", "DELETE", "MOVE"], None);
+	let elems = parts_ref.tag_elems();
+
+	// -- Check
+	assert!(matches!(
+		elems[0].deserialize_as::<FileDirective>(),
+		Err(Error::InvalidAttr { tag_name, key, value, .. }) if tag_name == "FILE" && key == "line" && value == "twelve"
+	));
+	assert!(matches!(
+		elems[1].deserialize_as::<Op>(),
+		Err(Error::InvalidElem { tag_name, cause }) if tag_name == "DELETE" && cause.contains("path")
+	));
+	assert!(matches!(
+		elems[2].deserialize_as::<Op>(),
+		Err(Error::InvalidElem { tag_name, cause }) if tag_name == "MOVE" && cause.contains("MOVE")
+	));
+
+	Ok(())
+}
@@ -0,0 +1,94 @@
+//! Non-fatal reports of structural problems found during extraction.
+
+use crate::Error;
+use serde::Serialize;
+use std::fmt;
+
+/// The kind of structural problem reported by a `TagDiagnostic`.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum TagDiagnosticKind {
+	/// An opening tag without a close delimiter (e.g., `<FILE path="a.rs"` at the end of the input),
+	/// or, with `MalformedPolicy::Recover`, whose close delimiter comes after a later opening tag.
+	MalformedOpen,
+
+	/// An opening tag without a matching closing tag.
+	Unclosed,
+
+	/// A closing tag without a matching opening tag, before the opening tag where extraction stopped, if any.
+	OrphanClose,
+
+	/// An element whose closing boundary was synthesized by auto-close.
+	AutoClosed,
+}
+
+/// A structural problem found during extraction, with the tag name and its byte span in the input.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct TagDiagnostic {
+	pub kind: TagDiagnosticKind,
+
+	/// The tag name as spelled in the input.
+	pub tag_name: String,
+
+	/// The byte index of the first character of the reported span.
+	pub start_idx: usize,
+
+	/// The byte index of the last character of the reported span.
+	///
+	/// The span covers the opening tag prefix for `MalformedOpen`, the opening tag for `Unclosed`,
+	/// the closing tag for `OrphanClose`, and the whole element for `AutoClosed`.
+	pub end_idx: usize,
+}
+
+/// Constructors
+impl TagDiagnostic {
+	pub fn new(kind: TagDiagnosticKind, tag_name: impl Into<String>, start_idx: usize, end_idx: usize) -> Self {
+		TagDiagnostic {
+			kind,
+			tag_name: tag_name.into(),
+			start_idx,
+			end_idx,
+		}
+	}
+}
+
+/// Conversions
+impl TagDiagnostic {
+	/// Returns the strict extraction error for this diagnostic, or `None` for an `AutoClosed` element,
+	/// whose recovery was requested through the options.
+	pub(crate) fn into_error(self) -> Option<Error> {
+		let TagDiagnostic {
+			kind,
+			tag_name: name,
+			start_idx,
+			..
+		} = self;
+
+		match kind {
+			TagDiagnosticKind::MalformedOpen => Some(Error::MalformedOpenTag { name, start_idx }),
+			TagDiagnosticKind::Unclosed => Some(Error::UnclosedTag { name, start_idx }),
+			TagDiagnosticKind::OrphanClose => Some(Error::UnexpectedClosingTag { name, start_idx }),
+			TagDiagnosticKind::AutoClosed => None,
+		}
+	}
+}
+
+// region:    --- Display
+
+impl fmt::Display for TagDiagnostic {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let description = match self.kind {
+			TagDiagnosticKind::MalformedOpen => "opening tag has no close delimiter",
+			TagDiagnosticKind::Unclosed => "opening tag has no matching closing tag",
+			TagDiagnosticKind::OrphanClose => "closing tag has no matching opening tag",
+			TagDiagnosticKind::AutoClosed => "element was closed without a closing tag",
+		};
+
+		write!(
+			f,
+			"{description}: `{}` at bytes {}..={}",
+			self.tag_name, self.start_idx, self.end_idx
+		)
+	}
+}
+
+// endregion: --- Display
@@ -0,0 +1,90 @@
+//! Reusable extractor holding tag patterns compiled once for repeated extraction.
+#![doc = include_str!("../../docs/rustdoc/tag/tag_extractor.md")]
+
+use crate::tag::support::TagScanner;
+use crate::tag::{Part, Parts, PartsRef, TagNameMatcher, TagOptions, TagRefIter, TagStreamParser};
+use std::sync::Arc;
+
+/// An extractor built once from tag names and options, and reused for any number of inputs.
+///
+/// The compiled patterns are shared behind an `Arc`, so cloning is cheap and the extractor
+/// is `Send + Sync`, ready to be shared across threads.
+#[derive(Clone)]
+pub struct TagExtractor {
+	scanner: Arc<TagScanner>,
+	options: TagOptions,
+}
+
+/// Constructors
+impl TagExtractor {
+	/// Creates a new `TagExtractor` for the given tag names.
+	///
+	/// # Arguments
+	///
+	/// * `tag_names` - The names of the tags to search for (e.g., &["FILE", "DATA"]).
+	/// * `options` - Parser configuration, or `None` for default options.
+	pub fn new(tag_names: &[&str], options: impl Into<TagOptions>) -> Self {
+		let options = options.into();
+		TagExtractor {
+			scanner: Arc::new(TagScanner::new(tag_names, options)),
+			options,
+		}
+	}
+
+	/// Creates a new `TagExtractor` accepting the opening tags whose names satisfy any of the matchers.
+	///
+	/// See `TagRefIter::new_with_matchers`.
+	pub fn new_with_matchers(matchers: &[TagNameMatcher], options: impl Into<TagOptions>) -> Self {
+		let options = options.into();
+		TagExtractor {
+			scanner: Arc::new(TagScanner::new_with_matchers(matchers, options)),
+			options,
+		}
+	}
+}
+
+/// Accessors
+impl TagExtractor {
+	pub fn options(&self) -> TagOptions {
+		self.options
+	}
+}
+
+/// Extraction
+impl TagExtractor {
+	/// Parses the input and returns the owned parts, like `tag::extract`.
+	pub fn extract(&self, input: &str) -> Parts {
+		let fence = self.options.fence_or_default();
+		let parts = self
+			.iter(input)
+			.map(|part_ref| Part::from_ref_in_fence(part_ref, &fence))
+			.collect();
+
+		Parts { parts }
+	}
+
+	/// Parses the input and returns references into it, like `tag::extract_refs`.
+	pub fn extract_refs<'a>(&self, input: &'a str) -> PartsRef<'a> {
+		let parts = self.iter(input).collect();
+
+		PartsRef { parts }
+	}
+
+	/// Returns an iterator over the parts of the input, like `TagRefIter::new`.
+	pub fn iter<'a>(&self, input: &'a str) -> TagRefIter<'a> {
+		TagRefIter::new_with_scanner(input, self.scanner.clone(), self.options)
+	}
+
+	/// Returns a new `TagStreamParser` using the same compiled patterns and options.
+	pub fn stream_parser(&self) -> TagStreamParser {
+		TagStreamParser::new_with_scanner(self.scanner.clone(), self.options)
+	}
+}
+
+// region:    --- Tests
+
+#[path = "tag_extractor_tests.rs"]
+#[cfg(test)]
+mod tests;
+
+// endregion: --- Tests
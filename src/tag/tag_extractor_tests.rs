@@ -0,0 +1,95 @@
+//! Tests for `TagExtractor`.
+
+use super::TagExtractor;
+use crate::tag::{FENCE_BRACKETS, Part, PartRef, TagNameMatcher, TagOptions, extract, extract_refs};
+
+type Result<T> = core::result::Result<T, Box<dyn std::error::Error>>;
+
+#[test]
+fn test_tag_extractor_matches_free_functions() -> Result<()> {
+	// -- Setup & Fixtures
+	let tag_names = ["FILE", "DATA"];
+	let options = TagOptions::default().with_capture_text(true).with_auto_close(true);
+	let extractor = TagExtractor::new(&tag_names, options);
+	let inputs = [
+		r#"Before <FILE path="a.rs">fn a() {}</FILE> middle <DATA/> after"#,
+		"<FILE>first <DATA>second</DATA><FILE>unclosed",
+		"no tags",
+	];
+
+	// -- Exec & Check
+	for input in inputs {
+		assert_eq!(
+			extractor.extract(input),
+			extract(input, &tag_names, options),
+			"input {input:?}"
+		);
+		assert_eq!(
+			extractor.extract_refs(input).into_parts(),
+			extract_refs(input, &tag_names, options).into_parts(),
+			"input {input:?}"
+		);
+	}
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extractor_shared_across_threads() -> Result<()> {
+	// -- Setup & Fixtures
+	fn assert_send_sync<T: Send + Sync>() {}
+	assert_send_sync::<TagExtractor>();
+	let extractor = TagExtractor::new(&["FILE"], TagOptions::default().with_fence(FENCE_BRACKETS));
+
+	// -- Exec
+	let handles: Vec<_> = (0..4)
+		.map(|i| {
+			let extractor = extractor.clone();
+			std::thread::spawn(move || {
+				let input = format!("[[[FILE id={i}]]]content {i}[[[/FILE]]]");
+				extractor.extract(&input).into_tag_elems()
+			})
+		})
+		.collect();
+
+	// -- Check
+	for (i, handle) in handles.into_iter().enumerate() {
+		let elems = handle.join().map_err(|_| "thread should not panic")?;
+		assert_eq!(elems.len(), 1);
+		assert_eq!(elems[0].content, format!("content {i}"));
+	}
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_extractor_with_matchers_iter_and_stream_parser() -> Result<()> {
+	// -- Setup & Fixtures
+	let extractor = TagExtractor::new_with_matchers(&[TagNameMatcher::prefix("TOOL_")], None);
+	let input = "<TOOL_A>a</TOOL_A> <OTHER>b</OTHER> <TOOL_B>c</TOOL_B>";
+
+	// -- Exec
+	let iter_names: Vec<&str> = extractor
+		.iter(input)
+		.filter_map(|part| match part {
+			PartRef::TagElemRef(elem) => Some(elem.tag_name),
+			PartRef::Text(_) => None,
+		})
+		.collect();
+	let mut parser = extractor.stream_parser();
+	let mut stream_parts = parser.push(input);
+	stream_parts.extend(parser.finish());
+
+	// -- Check
+	assert_eq!(iter_names, ["TOOL_A", "TOOL_B"]);
+	let stream_names: Vec<&str> = stream_parts
+		.iter()
+		.filter_map(|part| match part {
+			Part::TagElem(elem) => Some(elem.tag.as_str()),
+			Part::Text(_) => None,
+		})
+		.collect();
+	assert_eq!(stream_names, ["TOOL_A", "TOOL_B"]);
+
+	Ok(())
+}
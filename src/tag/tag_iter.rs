@@ -1,13 +1,15 @@
 //! Iterator to extract structured `TagElem`s including content and parsed attributes.
 #![doc = include_str!("../../docs/rustdoc/tag/tag_iter.md")]
 
-use super::{Part, TagOptions, TagRefIter};
+use super::{Part, TagFence, TagNameMatcher, TagOptions, TagRefIter};
 
 /// Iterator that yields owned `Part` instances (`Text` or `TagElem`), found within a text
 /// based on specific tag names.
 /// It consumes the referenced elements from `TagRefIter` and converts them to owned types.
 pub struct TagIter<'a> {
 	tag_content_iter: TagRefIter<'a>,
+	/// The fence whose raw sections are removed from element content.
+	fence: TagFence,
 }
 
 impl<'a> TagIter<'a> {
@@ -23,7 +25,10 @@ impl<'a> TagIter<'a> {
 		let tag_names_vec: Vec<&'a str> = tag_names.to_vec();
 		let tag_content_iter = TagRefIter::new(input, &tag_names_vec, options);
 
-		Self { tag_content_iter }
+		Self {
+			tag_content_iter,
+			fence: options.fence_or_default(),
+		}
 	}
 
 	/// Creates a new `TagElemIter` configured to search for a single tag name.
@@ -38,13 +43,28 @@ impl<'a> TagIter<'a> {
 	pub fn new_single_tag(input: &'a str, tag_name: &'a str, options: impl Into<TagOptions>) -> Self {
 		Self::new(input, &[tag_name], options)
 	}
+
+	/// Creates a new `TagIter` accepting the opening tags whose names satisfy any of the matchers.
+	///
+	/// See `TagRefIter::new_with_matchers`.
+	pub fn new_with_matchers(input: &'a str, matchers: &[TagNameMatcher], options: impl Into<TagOptions>) -> Self {
+		let options = options.into();
+		let tag_content_iter = TagRefIter::new_with_matchers(input, matchers, options);
+
+		Self {
+			tag_content_iter,
+			fence: options.fence_or_default(),
+		}
+	}
 }
 
 impl Iterator for TagIter<'_> {
 	type Item = Part;
 
 	fn next(&mut self) -> Option<Self::Item> {
-		self.tag_content_iter.next().map(Part::from)
+		let part_ref = self.tag_content_iter.next()?;
+
+		Some(Part::from_ref_in_fence(part_ref, &self.fence))
 	}
 }
 
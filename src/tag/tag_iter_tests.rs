@@ -1,7 +1,6 @@
 //! Tests for the TagElemIter.
 
-use crate::tag::{FENCE_BRACKETS, Part, TagElem, TagIter, TagOptions};
-use std::collections::HashMap;
+use crate::tag::{Attrs, FENCE_BRACKETS, Part, TagElem, TagIter, TagOptions, TagSpan};
 
 type Result<T> = core::result::Result<T, Box<dyn std::error::Error>>;
 
@@ -25,6 +24,7 @@ fn test_support_tag_elem_iter_simple() -> Result<()> {
 			attrs: None,
 			content: "content1".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(10, 31),
 		})
 	);
 	assert_eq!(parts[2], Part::Text(" more text ".to_string()));
@@ -35,6 +35,7 @@ fn test_support_tag_elem_iter_simple() -> Result<()> {
 			attrs: None,
 			content: "content2".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(42, 63),
 		})
 	);
 	assert_eq!(parts[4], Part::Text(" final.".to_string()));
@@ -87,11 +88,14 @@ fn test_support_tag_elem_iter_with_attrs() -> Result<()> {
 	assert_eq!(parts.len(), 2);
 	assert_eq!(parts[0], Part::Text("Some ".to_string()));
 
-	let mut expected_attrs = HashMap::new();
-	expected_attrs.insert("path".to_string(), "a/b.txt".to_string());
-	expected_attrs.insert("flag".to_string(), "".to_string());
-	expected_attrs.insert("attr".to_string(), "123".to_string());
-	expected_attrs.insert("message".to_string(), "hello world".to_string());
+	let expected_attrs = [
+		("path", Some("a/b.txt")),
+		("flag", None),
+		("attr", Some("123")),
+		("message", Some("hello world")),
+	]
+	.into_iter()
+	.collect::<Attrs>();
 
 	assert_eq!(
 		parts[1],
@@ -100,6 +104,7 @@ fn test_support_tag_elem_iter_with_attrs() -> Result<()> {
 			attrs: Some(expected_attrs.clone()),
 			content: "value".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(5, 74),
 		})
 	);
 
@@ -143,13 +148,13 @@ fn test_support_tag_elem_iter_multiple_tag_names() -> Result<()> {
 			attrs: None,
 			content: "first".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(6, 22),
 		})
 	);
 
 	assert_eq!(parts[2], Part::Text(" Beta ".to_string()));
 
-	let mut expected_attrs = HashMap::new();
-	expected_attrs.insert("attr".to_string(), "ok".to_string());
+	let expected_attrs = Attrs::from([("attr", "ok")]);
 
 	assert_eq!(
 		parts[3],
@@ -158,6 +163,7 @@ fn test_support_tag_elem_iter_multiple_tag_names() -> Result<()> {
 			attrs: Some(expected_attrs),
 			content: "second".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(28, 53),
 		})
 	);
 
@@ -187,6 +193,7 @@ fn test_support_tag_elem_iter_self_closing_simple() -> Result<()> {
 			attrs: None,
 			content: "".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(0, 7),
 		})
 	);
 
@@ -206,9 +213,7 @@ fn test_support_tag_elem_iter_self_closing_with_attrs() -> Result<()> {
 	// -- Check
 	assert_eq!(parts.len(), 1);
 
-	let mut expected_attrs = HashMap::new();
-	expected_attrs.insert("path".to_string(), "a/b.txt".to_string());
-	expected_attrs.insert("id".to_string(), "123".to_string());
+	let expected_attrs = Attrs::from([("path", "a/b.txt"), ("id", "123")]);
 
 	assert_eq!(
 		parts[0],
@@ -217,6 +222,7 @@ fn test_support_tag_elem_iter_self_closing_with_attrs() -> Result<()> {
 			attrs: Some(expected_attrs),
 			content: "".to_string(),
 			auto_closed: false,
+			span: TagSpan::new(0, 30),
 		})
 	);
 
@@ -267,6 +273,7 @@ fn test_support_tag_elem_iter_bracket_fence_alternate_delimiters() -> Result<()>
 				attrs: None,
 				content: expected_content.to_string(),
 				auto_closed: false,
+				span: TagSpan::new(0, text.len()),
 			})]
 		);
 	}
@@ -314,12 +321,14 @@ fn test_tag_iter_auto_close_bracket_fence_same_name() -> Result<()> {
 				attrs: None,
 				content: "first".to_string(),
 				auto_closed: true,
+				span: TagSpan::new(0, 15),
 			}),
 			Part::TagElem(TagElem {
 				tag: "ITEM".to_string(),
 				attrs: None,
 				content: "second".to_string(),
 				auto_closed: false,
+				span: TagSpan::new(15, 42),
 			}),
 		]
 	);
@@ -0,0 +1,159 @@
+//! Defines rules for accepting tag names beyond an exact list.
+#![doc = include_str!("../../docs/rustdoc/tag/tag_name_matcher.md")]
+
+use std::fmt;
+use std::sync::Arc;
+
+/// A rule that decides which opening tag names are extracted.
+///
+/// `Exact` matches any name, while the other variants only match well-formed names (an ASCII letter
+/// or `_`, then ASCII letters, digits, `_`, `-`, `.`, or `:`). The closing tag of an element always
+/// uses the exact name that was opened.
+#[derive(Clone)]
+pub enum TagNameMatcher {
+	/// Matches a single name exactly (e.g., `FILE`).
+	Exact(String),
+
+	/// Matches names starting with the prefix (e.g., `TOOL_` for `TOOL_*`).
+	Prefix(String),
+
+	/// Matches names against a glob pattern, where `*` matches any sequence and `?` any single character.
+	Glob(String),
+
+	/// Matches names accepted by the predicate.
+	Predicate(Arc<dyn Fn(&str) -> bool + Send + Sync>),
+}
+
+/// Constructors
+impl TagNameMatcher {
+	pub fn exact(name: impl Into<String>) -> Self {
+		TagNameMatcher::Exact(name.into())
+	}
+
+	pub fn prefix(prefix: impl Into<String>) -> Self {
+		TagNameMatcher::Prefix(prefix.into())
+	}
+
+	pub fn glob(pattern: impl Into<String>) -> Self {
+		TagNameMatcher::Glob(pattern.into())
+	}
+
+	pub fn predicate(predicate: impl Fn(&str) -> bool + Send + Sync + 'static) -> Self {
+		TagNameMatcher::Predicate(Arc::new(predicate))
+	}
+}
+
+/// Matching
+impl TagNameMatcher {
+	/// Returns whether the tag name is accepted by this matcher.
+	pub fn matches(&self, name: &str) -> bool {
+		self.matches_with_case(name, false)
+	}
+
+	/// Returns whether the tag name is accepted, comparing `Exact`, `Prefix`, and `Glob` names regardless
+	/// of ASCII case when `ignore_case` is set. Predicates receive the name as spelled in the input.
+	pub(crate) fn matches_with_case(&self, name: &str, ignore_case: bool) -> bool {
+		match self {
+			TagNameMatcher::Exact(exact) if ignore_case => name.eq_ignore_ascii_case(exact),
+			TagNameMatcher::Exact(exact) => name == exact,
+			TagNameMatcher::Prefix(prefix) => name
+				.get(..prefix.len())
+				.is_some_and(|name_prefix| chars_eq(name_prefix, prefix, ignore_case)),
+			TagNameMatcher::Glob(pattern) => glob_matches(pattern, name, ignore_case),
+			TagNameMatcher::Predicate(predicate) => predicate(name),
+		}
+	}
+}
+
+// region:    --- Froms
+
+impl From<&str> for TagNameMatcher {
+	fn from(name: &str) -> Self {
+		TagNameMatcher::Exact(name.to_string())
+	}
+}
+
+impl From<String> for TagNameMatcher {
+	fn from(name: String) -> Self {
+		TagNameMatcher::Exact(name)
+	}
+}
+
+// endregion: --- Froms
+
+// region:    --- Debug
+
+impl fmt::Debug for TagNameMatcher {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			TagNameMatcher::Exact(name) => f.debug_tuple("Exact").field(name).finish(),
+			TagNameMatcher::Prefix(prefix) => f.debug_tuple("Prefix").field(prefix).finish(),
+			TagNameMatcher::Glob(pattern) => f.debug_tuple("Glob").field(pattern).finish(),
+			TagNameMatcher::Predicate(_) => f.write_str("Predicate(..)"),
+		}
+	}
+}
+
+// endregion: --- Debug
+
+// region:    --- Support
+
+/// Matches `name` against a glob `pattern` supporting `*` and `?`, backtracking only to the last `*`.
+fn glob_matches(pattern: &str, name: &str, ignore_case: bool) -> bool {
+	let pattern: Vec<char> = pattern.chars().collect();
+	let name: Vec<char> = name.chars().collect();
+
+	let (mut pattern_idx, mut name_idx) = (0, 0);
+	// The last `*` position in the pattern and the name index it currently absorbs up to.
+	let mut star: Option<(usize, usize)> = None;
+
+	while name_idx < name.len() {
+		match pattern.get(pattern_idx) {
+			Some('*') => {
+				star = Some((pattern_idx, name_idx));
+				pattern_idx += 1;
+			}
+			Some(&c) if c == '?' || char_eq(c, name[name_idx], ignore_case) => {
+				pattern_idx += 1;
+				name_idx += 1;
+			}
+			_ => match star {
+				// Let the last `*` absorb one more character and retry.
+				Some((star_pattern_idx, star_name_idx)) => {
+					star = Some((star_pattern_idx, star_name_idx + 1));
+					pattern_idx = star_pattern_idx + 1;
+					name_idx = star_name_idx + 1;
+				}
+				None => return false,
+			},
+		}
+	}
+
+	pattern[pattern_idx..].iter().all(|&c| c == '*')
+}
+
+fn char_eq(c: char, other: char, ignore_case: bool) -> bool {
+	if ignore_case {
+		c.eq_ignore_ascii_case(&other)
+	} else {
+		c == other
+	}
+}
+
+fn chars_eq(value: &str, other: &str, ignore_case: bool) -> bool {
+	if ignore_case {
+		value.eq_ignore_ascii_case(other)
+	} else {
+		value == other
+	}
+}
+
+// endregion: --- Support
+
+// region:    --- Tests
+
+#[path = "tag_name_matcher_tests.rs"]
+#[cfg(test)]
+mod tests;
+
+// endregion: --- Tests
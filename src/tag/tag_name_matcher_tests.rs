@@ -0,0 +1,97 @@
+//! Tests for `TagNameMatcher` and its use by the tag iterators.
+
+use super::TagNameMatcher;
+use crate::tag::{PartRef, TagOptions, TagRefIter, extract_with_matchers};
+
+type Result<T> = core::result::Result<T, Box<dyn std::error::Error>>;
+
+#[test]
+fn test_tag_name_matcher_matches() -> Result<()> {
+	// -- Setup & Fixtures
+	let cases = [
+		(TagNameMatcher::exact("FILE"), "FILE", true),
+		(TagNameMatcher::exact("FILE"), "FILES", false),
+		(TagNameMatcher::prefix("TOOL_"), "TOOL_READ", true),
+		(TagNameMatcher::prefix("TOOL_"), "TOOL", false),
+		(TagNameMatcher::glob("TOOL_*"), "TOOL_", true),
+		(TagNameMatcher::glob("*_FILE"), "READ_FILE", true),
+		(TagNameMatcher::glob("T?OL_*_V*"), "TOOL_READ_V2", true),
+		(TagNameMatcher::glob("T?OL_*_V*"), "TOOL_READ", false),
+		(TagNameMatcher::glob("A*B*C"), "AxxBxxBxC", true),
+		(TagNameMatcher::glob("A*B*C"), "AxxBxxBxD", false),
+		(TagNameMatcher::predicate(|name| name.len() == 3), "ABC", true),
+		(TagNameMatcher::predicate(|name| name.len() == 3), "ABCD", false),
+	];
+
+	// -- Exec & Check
+	for (matcher, name, expected) in cases {
+		assert_eq!(matcher.matches(name), expected, "{matcher:?} on {name:?}");
+	}
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_name_matcher_closing_uses_opened_name() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<TOOL_A>a</TOOL_B> x </TOOL_A> <TOOL_B/> <TOOLBOX>no</TOOLBOX>";
+	let matchers = [TagNameMatcher::glob("TOOL_*")];
+
+	// -- Exec
+	let parts: Vec<PartRef> = TagRefIter::new_with_matchers(input, &matchers, TagOptions::default()).collect();
+
+	// -- Check
+	let tags: Vec<_> = parts
+		.into_iter()
+		.filter_map(|part| match part {
+			PartRef::TagElemRef(tag) => Some(tag),
+			PartRef::Text(_) => None,
+		})
+		.collect();
+	assert_eq!(tags.len(), 2);
+	assert_eq!(tags[0].tag_name, "TOOL_A");
+	assert_eq!(tags[0].content, "a</TOOL_B> x ");
+	assert_eq!(tags[1].tag_name, "TOOL_B");
+	assert_eq!(tags[1].content, "");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_name_matcher_extract_mixed_matchers() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<PLAN>p</PLAN><TOOL_READ>r</TOOL_READ><x>y</x><NOTE>n</NOTE>";
+	let matchers = [
+		TagNameMatcher::from("PLAN"),
+		TagNameMatcher::prefix("TOOL_"),
+		TagNameMatcher::predicate(|name| name == "NOTE"),
+	];
+
+	// -- Exec
+	let parts = extract_with_matchers(input, &matchers, TagOptions::default().with_capture_text(true));
+
+	// -- Check
+	let names: Vec<&str> = parts.tag_elems().iter().map(|elem| elem.tag.as_str()).collect();
+	assert_eq!(names, ["PLAN", "TOOL_READ", "NOTE"]);
+	assert_eq!(parts.into_texts(), ["<x>y</x>"]);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_name_matcher_case_insensitive_names() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<tool_read>r</TOOL_READ><Tool_Write>w</tool_write>";
+	let matchers = [TagNameMatcher::prefix("TOOL_")];
+	let options = TagOptions::default().with_case_insensitive_names(true);
+
+	// -- Exec
+	let parts = extract_with_matchers(input, &matchers, options);
+
+	// -- Check
+	let names: Vec<&str> = parts.tag_elems().iter().map(|elem| elem.tag.as_str()).collect();
+	assert_eq!(names, ["tool_read", "Tool_Write"]);
+	assert_eq!(parts.tag_elems()[1].normalized_name(), "TOOL_WRITE");
+
+	Ok(())
+}
@@ -1,4 +1,11 @@
-use std::collections::HashMap;
+use crate::Result;
+use crate::tag::support::{missing_attr, parse_attr, parse_attr_bool};
+use crate::tag::tag_deserializer::{TagElemDeserializer, deserialize_elem};
+use crate::tag::{AttrsRef, TagElemSpans, TagFence, TagSpan};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
 
 /// Represents a segment of text identified by start and end tags,
 /// potentially including parameters in the start marker.
@@ -11,8 +18,8 @@ pub struct TagElemRef<'a> {
 	/// The name of the tag (e.g., "SOME_MARKER").
 	pub tag_name: &'a str,
 
-	/// Optional attributes map.
-	pub attrs: Option<HashMap<&'a str, &'a str>>,
+	/// Optional attributes, in source order.
+	pub attrs: Option<AttrsRef<'a>>,
 
 	/// The content string between the opening and closing tags.
 	pub content: &'a str,
@@ -25,4 +32,97 @@ pub struct TagElemRef<'a> {
 
 	/// The byte index of the closing '>' of the end tag in the original string.
 	pub end_idx: usize,
+
+	/// The byte spans of the opening tag, name, attributes, content, and closing tag in the original string.
+	pub spans: TagElemSpans,
+}
+
+/// Accessors
+impl<'a> TagElemRef<'a> {
+	/// Returns the tag name in ASCII uppercase, so names matched regardless of case compare equal.
+	///
+	/// `tag_name` keeps the spelling used in the input (e.g., `file` for `<file>`).
+	pub fn normalized_name(&self) -> Cow<'a, str> {
+		if self.tag_name.bytes().any(|b| b.is_ascii_lowercase()) {
+			Cow::Owned(self.tag_name.to_ascii_uppercase())
+		} else {
+			Cow::Borrowed(self.tag_name)
+		}
+	}
+
+	/// Returns the byte span of the whole element, from `start_idx` through `end_idx`.
+	///
+	/// Resolve it to line and column positions with a `LineIndex` of the input.
+	pub fn span(&self) -> TagSpan {
+		TagSpan::new(self.start_idx, self.end_idx + 1)
+	}
+
+	/// Returns the content without the delimiters of the raw sections of `fence`,
+	/// borrowed from the input when it has none.
+	///
+	/// `content` keeps the raw sections as written (e.g., `<![CDATA[x]]>`), while the owned `TagElem` content
+	/// returned by the extraction APIs is unwrapped.
+	pub fn unwrapped_content(&self, fence: &TagFence) -> Cow<'a, str> {
+		fence.unwrap_raw_sections(self.content)
+	}
+}
+
+/// Typed Attributes
+impl TagElemRef<'_> {
+	/// Returns the value of the attribute converted with `FromStr`, or `None` when the key is absent.
+	///
+	/// Fails with `Error::InvalidAttr` when the value does not convert, or when the attribute is a flag without value.
+	pub fn attr<T>(&self, key: &str) -> Result<Option<T>>
+	where
+		T: FromStr,
+		T::Err: fmt::Display,
+	{
+		parse_attr(
+			self.tag_name,
+			key,
+			self.attrs.as_ref().and_then(|attrs| attrs.lookup(key)),
+		)
+	}
+
+	/// Returns the value of the attribute converted with `FromStr`, failing with `Error::MissingAttr` when the key is absent.
+	pub fn required_attr<T>(&self, key: &str) -> Result<T>
+	where
+		T: FromStr,
+		T::Err: fmt::Display,
+	{
+		self.attr(key)?.ok_or_else(|| missing_attr(self.tag_name, key))
+	}
+
+	/// Returns the value of the attribute converted with `FromStr`, or `default` when the key is absent.
+	pub fn attr_or<T>(&self, key: &str, default: T) -> Result<T>
+	where
+		T: FromStr,
+		T::Err: fmt::Display,
+	{
+		Ok(self.attr(key)?.unwrap_or(default))
+	}
+
+	/// Returns the value of the attribute as a bool, or `None` when the key is absent.
+	///
+	/// Accepts `true`/`false`, `yes`/`no`, and `1`/`0` regardless of ASCII case, and a flag without value
+	/// (e.g., `<FILE overwrite>`) is `true`. Fails with `Error::InvalidAttr` for any other value.
+	pub fn attr_bool(&self, key: &str) -> Result<Option<bool>> {
+		parse_attr_bool(
+			self.tag_name,
+			key,
+			self.attrs.as_ref().and_then(|attrs| attrs.lookup(key)),
+		)
+	}
+}
+
+/// Deserialization
+impl<'a> TagElemRef<'a> {
+	/// Deserializes the element into a serde type, like `TagElem::deserialize_as`,
+	/// with `&'a str` fields borrowing from the input when values are not decoded.
+	///
+	/// The `$content` field is `content` as written, including raw sections. Deserialize the owned `TagElem`
+	/// returned by the extraction APIs to have them unwrapped.
+	pub fn deserialize_as<T: Deserialize<'a>>(&self) -> Result<T> {
+		deserialize_elem(TagElemDeserializer::from_elem_ref(self))
+	}
 }
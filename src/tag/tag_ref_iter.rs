@@ -1,8 +1,10 @@
 //! Iterator for extracting marked content sections like <TAG>...</TAG> from text.
 #![doc = include_str!("../../docs/rustdoc/tag/tag_ref_iter.md")]
 
-use crate::tag::support::parse_attrs_ref;
-use crate::tag::{TagElemRef, TagFence, TagOptions};
+use crate::tag::support::{ElemEnd, TagScanner};
+use crate::tag::{MalformedPolicy, TagDiagnostic, TagDiagnosticKind, TagElemRef, TagFence, TagNameMatcher, TagOptions};
+use std::collections::HashSet;
+use std::sync::Arc;
 
 /// Represents a part of parsed content as a reference, either plain text or a tag element reference.
 #[derive(Debug, PartialEq)]
@@ -15,6 +17,7 @@ pub enum PartRef<'a> {
 }
 
 /// Precomputed tag patterns derived from the tag name for efficient searching.
+#[derive(Debug, Clone)]
 pub struct TagPattern {
 	/// The original tag name (e.g., "FILE").
 	pub name: String,
@@ -63,25 +66,21 @@ impl TagPattern {
 	}
 }
 
-fn find_next_match<'a>(input: &str, patterns: impl IntoIterator<Item = &'a str>) -> Option<(usize, usize)> {
-	let mut selected = None;
-
-	for pattern in patterns {
-		if let Some(start_idx) = input.find(pattern) {
-			selected = match selected {
-				None => Some((start_idx, pattern.len())),
-				Some((existing_idx, existing_len)) => {
-					if start_idx < existing_idx || (start_idx == existing_idx && pattern.len() > existing_len) {
-						Some((start_idx, pattern.len()))
-					} else {
-						Some((existing_idx, existing_len))
-					}
-				}
-			};
-		}
-	}
+/// Support
+impl TagPattern {
+	/// Returns the maximum number of bytes needed from a start index to decide an opening or closing tag there.
+	pub(crate) fn lookahead(&self) -> usize {
+		let after_prefix_len = self
+			.close_delims
+			.iter()
+			.map(|close_delim| close_delim.len())
+			.chain([self.closing_tag_prefix.len(), 1])
+			.max()
+			.unwrap_or(1);
+		let end_tag_len = self.end_tags.iter().map(String::len).max().unwrap_or(0);
 
-	selected
+		(self.start_tag_prefix.len() + after_prefix_len).max(end_tag_len)
+	}
 }
 
 /// An iterator that finds and extracts `PartRef` sections from a string slice.
@@ -92,11 +91,21 @@ pub struct TagRefIter<'a> {
 	input: &'a str,
 	current_pos: usize,
 	last_processed_idx: usize,
-	tag_patterns: Vec<TagPattern>,
+	scanner: Arc<TagScanner>,
 	pending_tag: Option<TagElemRef<'a>>,
 	finished: bool,
 	capture_text: bool,
-	auto_close: bool,
+	malformed_policy: MalformedPolicy,
+	/// The start of the first opening tag found without a close delimiter, if any.
+	unterminated_from: Option<usize>,
+	/// The start of the opening tag where extraction stopped with `MalformedPolicy::Stop`, if any.
+	stopped_at: Option<usize>,
+	/// The names found without a closing tag, which no later element of the same name has either.
+	unclosed_names: HashSet<String>,
+	/// The structural problems found so far, when diagnostics are enabled.
+	diagnostics: Option<Vec<TagDiagnostic>>,
+	/// Whether openings inside raw sections are ignored, as in element content.
+	skip_raw_sections: bool,
 }
 
 /// Constructors
@@ -110,233 +119,238 @@ impl<'a> TagRefIter<'a> {
 	/// * `options` - Parser configuration, or `None` for default options.
 	pub fn new(input: &'a str, tag_names: &[&str], options: impl Into<TagOptions>) -> Self {
 		let options = options.into();
-		let tag_infos = tag_names
-			.iter()
-			.map(|&name| TagPattern::new(name, options.fence_or_default()))
-			.collect();
+		Self::new_with_scanner(input, Arc::new(TagScanner::new(tag_names, options)), options)
+	}
+
+	/// Creates a new `TagRefIter` accepting the opening tags whose names satisfy any of the matchers.
+	///
+	/// Exact matchers behave like the names given to `Self::new`. The closing tag of an element
+	/// must use the exact name that was opened.
+	///
+	/// # Arguments
+	///
+	/// * `input` - The string slice to search within.
+	/// * `matchers` - The tag name matchers (e.g., `&[TagNameMatcher::prefix("TOOL_")]`).
+	/// * `options` - Parser configuration, or `None` for default options.
+	pub fn new_with_matchers(input: &'a str, matchers: &[TagNameMatcher], options: impl Into<TagOptions>) -> Self {
+		let options = options.into();
+		let scanner = TagScanner::new_with_matchers(matchers, options);
+		Self::new_with_scanner(input, Arc::new(scanner), options)
+	}
+
+	/// Creates a new `TagRefIter` sharing an already built scanner (see `TagExtractor`).
+	pub(crate) fn new_with_scanner(input: &'a str, scanner: Arc<TagScanner>, options: TagOptions) -> Self {
 		TagRefIter {
 			input,
 			current_pos: 0,
 			last_processed_idx: 0,
-			tag_patterns: tag_infos,
+			scanner,
 			pending_tag: None,
 			finished: false,
 			capture_text: options.capture_text(),
-			auto_close: options.auto_close,
+			malformed_policy: options.malformed_policy,
+			unterminated_from: None,
+			stopped_at: None,
+			unclosed_names: HashSet::new(),
+			diagnostics: None,
+			skip_raw_sections: false,
 		}
 	}
 }
 
-/// Parsing support
-impl<'a> TagRefIter<'a> {
-	fn find_next_opening(&self, from_idx: usize) -> Option<(usize, &TagPattern)> {
-		let mut current_pos = from_idx;
-
-		while current_pos < self.input.len() {
-			let remaining_input = &self.input[current_pos..];
-			let mut selected: Option<(usize, &TagPattern)> = None;
-
-			for tag_info in &self.tag_patterns {
-				if let Some(offset) = remaining_input.find(&tag_info.start_tag_prefix) {
-					let start_idx = current_pos + offset;
-
-					selected = match selected {
-						None => Some((start_idx, tag_info)),
-						Some((existing_idx, existing_tag)) => {
-							if start_idx < existing_idx
-								|| (start_idx == existing_idx && tag_info.name.len() > existing_tag.name.len())
-							{
-								Some((start_idx, tag_info))
-							} else {
-								Some((existing_idx, existing_tag))
-							}
-						}
-					};
-				}
-			}
-
-			let (start_idx, tag_info) = selected?;
-			let after_prefix_idx = start_idx + tag_info.start_tag_prefix.len();
-			let remaining_after_prefix = &self.input[after_prefix_idx..];
-			let valid_after_prefix = tag_info
-				.close_delims
-				.iter()
-				.any(|close_delim| remaining_after_prefix.starts_with(close_delim))
-				|| remaining_after_prefix.starts_with(&tag_info.closing_tag_prefix)
-				|| matches!(
-					self.input.as_bytes().get(after_prefix_idx),
-					Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r')
-				);
+/// Chainable Setters
+impl TagRefIter<'_> {
+	/// Enables the collection of `TagDiagnostic`s for malformed, unclosed, orphan, and auto-closed tags.
+	///
+	/// Diagnostics do not change the yielded parts. Read them with `diagnostics` or `into_diagnostics`
+	/// once the iteration is done (e.g., after collecting from `by_ref()`).
+	pub fn with_diagnostics(mut self) -> Self {
+		self.diagnostics = Some(Vec::new());
+		self
+	}
 
-			if valid_after_prefix {
-				return Some((start_idx, tag_info));
-			}
+	/// Ignores the openings inside raw sections, so the input is searched like element content.
+	pub(crate) fn with_raw_sections_skipped(mut self) -> Self {
+		self.skip_raw_sections = true;
+		self
+	}
+}
 
-			current_pos = start_idx + 1;
-		}
+/// Accessors
+impl TagRefIter<'_> {
+	/// Returns the diagnostics collected so far, in source order once the iteration is done.
+	pub fn diagnostics(&self) -> &[TagDiagnostic] {
+		self.diagnostics.as_deref().unwrap_or_default()
+	}
 
-		None
+	/// Consumes the iterator and returns the collected diagnostics.
+	pub fn into_diagnostics(self) -> Vec<TagDiagnostic> {
+		self.diagnostics.unwrap_or_default()
 	}
+}
 
+/// Parsing support
+impl<'a> TagRefIter<'a> {
 	/// Internal method to find the next tag element.
 	fn find_next_tag(&mut self) -> Option<TagElemRef<'a>> {
-		while self.current_pos < self.input.len() {
-			// --- Find the start tag prefix ---
-			let remaining_input = &self.input[self.current_pos..];
-			let mut selected: Option<(usize, &TagPattern)> = None;
-
-			for tag_info in &self.tag_patterns {
-				if let Some(offset) = remaining_input.find(&tag_info.start_tag_prefix) {
-					let start_idx = self.current_pos + offset;
-
-					selected = match selected {
-						None => Some((start_idx, tag_info)),
-						Some((existing_idx, existing_tag)) => {
-							if start_idx < existing_idx
-								|| (start_idx == existing_idx && tag_info.name.len() > existing_tag.name.len())
-							{
-								Some((start_idx, tag_info))
-							} else {
-								Some((existing_idx, existing_tag))
-							}
-						}
-					};
+		let tag = self.find_next_tag_inner();
+
+		if self.diagnostics.is_some() {
+			match &tag {
+				Some(tag) => {
+					self.record_orphan_closes(self.current_pos, tag.start_idx);
+					if tag.auto_closed {
+						let (tag_name, start_idx, end_idx) = (tag.tag_name, tag.start_idx, tag.end_idx);
+						record(
+							&mut self.diagnostics,
+							TagDiagnosticKind::AutoClosed,
+							tag_name,
+							start_idx,
+							end_idx,
+						);
+					}
+				}
+				None => {
+					// The closing tags after a stopping opening tag were never matched, so they are not orphans.
+					let to_idx = self.stopped_at.unwrap_or(self.input.len());
+					self.record_orphan_closes(self.current_pos, to_idx);
+					if let Some(diagnostics) = &mut self.diagnostics {
+						diagnostics.sort_by_key(|diagnostic| diagnostic.start_idx);
+					}
 				}
 			}
+		}
 
-			let (start_idx, tag_info) = selected?;
-
-			let after_prefix_idx = start_idx + tag_info.start_tag_prefix.len();
-
-			// --- Validate character after prefix (must be the closing delimiter, closing prefix, or whitespace) ---
-			let remaining_after_prefix = &self.input[after_prefix_idx..];
-			let valid_after_prefix = tag_info
-				.close_delims
-				.iter()
-				.any(|close_delim| remaining_after_prefix.starts_with(close_delim))
-				|| remaining_after_prefix.starts_with(&tag_info.closing_tag_prefix)
-				|| matches!(
-					self.input.as_bytes().get(after_prefix_idx),
-					Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r')
-				);
-			if !valid_after_prefix {
-				// It's a different tag (e.g., <TAG_NAMEXXX). Advance past the '<' and continue searching.
-				self.current_pos = start_idx + 1;
-				continue;
-			}
-
-			// --- Find the end of the opening tag ---
-			let remaining_from_start = &self.input[start_idx..];
-			let (open_tag_end_offset, close_delim_len) =
-				match find_next_match(remaining_from_start, tag_info.close_delims.iter().copied()) {
-					Some(match_info) => match_info,
-					None => {
-						// Malformed open tag (no '>'). Stop searching. Consider advancing past '<'?
-						// For simplicity, we stop here. A more robust parser might skip.
-						return None;
-					}
-				};
-			let open_tag_close_start_idx = start_idx + open_tag_end_offset;
-			let open_tag_end_idx = open_tag_close_start_idx + close_delim_len - 1;
+		// --- Update position for next search ---
+		if let Some(tag) = &tag {
+			self.current_pos = tag.end_idx + 1;
+		}
 
-			let tag_name_len = tag_info.name.len();
-			let tag_name_start_idx = after_prefix_idx - tag_name_len;
-			let tag_name = &self.input[tag_name_start_idx..after_prefix_idx];
+		tag
+	}
 
-			// --- Check for self-closing tag ---
-			let opening_tag_body = &self.input[after_prefix_idx..open_tag_close_start_idx];
-			let self_closing = opening_tag_body.ends_with(&tag_info.self_closing_suffix);
+	fn find_next_tag_inner(&mut self) -> Option<TagElemRef<'a>> {
+		let input = self.input;
+		let scanner = &self.scanner;
+		let mut search_pos = self.current_pos;
 
-			// --- Extract Parameters (exclude self-closing slash) ---
-			let attrs_section = if self_closing {
-				opening_tag_body[..opening_tag_body.len() - tag_info.self_closing_suffix.len()].trim_end()
+		loop {
+			// --- Find the next valid opening tag ---
+			let (start_idx, tag_info) = if self.skip_raw_sections {
+				scanner.find_next_opening_outside_raw(input, search_pos)?
 			} else {
-				opening_tag_body
+				scanner.find_next_opening(input, search_pos, None)?
 			};
-			let attrs = parse_attrs_ref(Some(attrs_section));
+			let name_end_idx = start_idx + tag_info.start_tag_prefix.len();
+			let tag_name = &input[name_end_idx - tag_info.name.len()..name_end_idx];
 
-			if self_closing {
-				// Self-closing: no content, no closing tag search
-				let end_idx = open_tag_end_idx;
-				self.current_pos = end_idx + 1;
-				return Some(TagElemRef {
+			// --- Find the end of the opening tag ---
+			// Malformed open tag (no '>', or a '>' past a later opening tag when recovering). We stop here, or skip
+			// past its prefix with the recover policy.
+			// Past an open tag without '>', no later opening tag can have a close delimiter either.
+			let open_tag = if self
+				.unterminated_from
+				.is_some_and(|unterminated_idx| start_idx >= unterminated_idx)
+			{
+				None
+			} else {
+				scanner.parse_open_tag(input, start_idx, &tag_info)
+			};
+			if open_tag.is_none() {
+				self.unterminated_from = Some(start_idx);
+			}
+			// An opening tag interrupted by a later opening is malformed only with the recover policy.
+			let recover = self.malformed_policy == MalformedPolicy::Recover;
+			let open_tag = open_tag.filter(|open_tag| {
+				!(recover && scanner.is_interrupted_open_tag(input, start_idx, &tag_info, open_tag))
+			});
+			let Some(open_tag) = open_tag else {
+				let end_idx = name_end_idx - 1;
+				record(
+					&mut self.diagnostics,
+					TagDiagnosticKind::MalformedOpen,
 					tag_name,
-					attrs,
-					content: "",
-					auto_closed: false,
 					start_idx,
 					end_idx,
-				});
-			}
-
-			// --- Find the closing tag ---
-			let search_after_open_tag_idx = open_tag_close_start_idx + close_delim_len;
-			if search_after_open_tag_idx >= self.input.len() && !self.auto_close {
-				// Reached end of input before finding closing tag
-				return None;
-			}
-
-			let remaining_after_open = &self.input[search_after_open_tag_idx..];
-			let close_tag = find_next_match(remaining_after_open, tag_info.end_tags.iter().map(String::as_str));
-			if self.auto_close
-				&& let Some((next_opening_idx, _)) = self.find_next_opening(search_after_open_tag_idx)
-				&& close_tag
-					.map(|(close_tag_start_offset, _)| search_after_open_tag_idx + close_tag_start_offset)
-					.is_none_or(|close_tag_start_idx| next_opening_idx < close_tag_start_idx)
-			{
-				let content = &self.input[open_tag_end_idx + 1..next_opening_idx];
-				self.current_pos = next_opening_idx;
+				);
+				match self.malformed_policy {
+					MalformedPolicy::Stop => {
+						self.stopped_at = Some(start_idx);
+						return None;
+					}
+					MalformedPolicy::Recover => {
+						search_pos = name_end_idx;
+						continue;
+					}
+				}
+			};
 
-				return Some(TagElemRef {
+			// --- Find the closing tag (none for self-closing) ---
+			let end = if open_tag.self_closing {
+				ElemEnd::Eof
+			} else {
+				let mut search = scanner.elem_end_search(input, start_idx, &tag_info, &open_tag);
+				let unclosed_name = scanner.unclosed_name_key(&tag_info, &search);
+				if unclosed_name.is_some_and(|name| self.unclosed_names.contains(name)) {
+					ElemEnd::Unclosed
+				} else {
+					let end = scanner.find_element_end(input, &tag_info, &mut search, true);
+					if let (ElemEnd::Unclosed, Some(name)) = (&end, unclosed_name) {
+						self.unclosed_names.insert(name.to_string());
+					}
+					end
+				}
+			};
+			if let ElemEnd::Unclosed = end {
+				let end_idx = open_tag.end_idx;
+				record(
+					&mut self.diagnostics,
+					TagDiagnosticKind::Unclosed,
 					tag_name,
-					attrs,
-					content,
-					auto_closed: true,
 					start_idx,
-					end_idx: next_opening_idx - 1,
-				});
-			}
-			let (close_tag_start_offset, close_tag_len) = match close_tag {
-				Some(close_tag) => close_tag,
-				None if self.auto_close => {
-					let content = &self.input[open_tag_end_idx + 1..];
-					self.current_pos = self.input.len();
-
-					return Some(TagElemRef {
-						tag_name,
-						attrs,
-						content,
-						auto_closed: true,
-						start_idx,
-						end_idx: self.input.len() - 1,
-					});
+					end_idx,
+				);
+				match self.malformed_policy {
+					MalformedPolicy::Stop => {
+						self.stopped_at = Some(start_idx);
+						return None;
+					}
+					MalformedPolicy::Recover => {
+						search_pos = open_tag.end_idx + 1;
+						continue;
+					}
 				}
-				None => return None,
-			};
-			let close_tag_start_idx = search_after_open_tag_idx + close_tag_start_offset;
-			// Corrected end_idx calculation: it's the index of the '>' of the closing tag
-			// The end index should be the index of the last character of the closing tag '>'
-			let end_idx = close_tag_start_idx + close_tag_len - 1;
-
-			// --- Extract Content ---
-			let content = &self.input[open_tag_end_idx + 1..close_tag_start_idx];
+			}
 
-			// --- Update position for next search ---
-			// The next search should start right after the closing tag
-			self.current_pos = end_idx + 1;
+			return scanner.build_elem_ref(input, start_idx, &tag_info, &open_tag, &end);
+		}
+	}
 
-			// --- Return the found item ---
-			return Some(TagElemRef {
+	/// Records the closing tags found between elements, which have no matching opening tag.
+	fn record_orphan_closes(&mut self, from_idx: usize, to_idx: usize) {
+		for (start_idx, tag_name, len) in self.scanner.find_closing_tags(self.input, from_idx, to_idx) {
+			let end_idx = start_idx + len - 1;
+			record(
+				&mut self.diagnostics,
+				TagDiagnosticKind::OrphanClose,
 				tag_name,
-				attrs,
-				content,
-				auto_closed: false,
 				start_idx,
 				end_idx,
-			});
+			);
 		}
+	}
+}
 
-		None
+/// Records a diagnostic, when diagnostics are enabled.
+fn record(
+	diagnostics: &mut Option<Vec<TagDiagnostic>>,
+	kind: TagDiagnosticKind,
+	tag_name: &str,
+	start_idx: usize,
+	end_idx: usize,
+) {
+	if let Some(diagnostics) = diagnostics {
+		diagnostics.push(TagDiagnostic::new(kind, tag_name, start_idx, end_idx));
 	}
 }
 
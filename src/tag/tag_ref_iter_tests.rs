@@ -1,8 +1,11 @@
 //! Tests for the TagContentIterator.
 
 use super::{PartRef, TagElemRef, TagRefIter};
-use crate::tag::{FENCE_BRACKETS, TagOptions, extract_refs};
-use std::collections::HashMap;
+use crate::tag::{
+	AttrsRef, FENCE_BRACKETS, FENCE_XML, MalformedPolicy, RAW_SECTION_CDATA, TagAttrSpan, TagDiagnosticKind,
+	TagElemSpans, TagNameMatcher, TagOptions, TagSpan, extract_refs,
+};
+use std::borrow::Cow;
 use std::error::Error;
 // For tests, using a simple Result alias is often sufficient.
 type Result<T> = core::result::Result<T, Box<dyn Error>>;
@@ -42,6 +45,13 @@ fn test_support_tag_content_iter_simple() -> Result<()> {
 			auto_closed: false,
 			start_idx: 10,
 			end_idx: 29,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(10, 16),
+				name: TagSpan::new(11, 15),
+				attrs: vec![],
+				content: TagSpan::new(16, 23),
+				close_tag: Some(TagSpan::new(23, 30)),
+			},
 		}
 	);
 
@@ -69,7 +79,7 @@ fn test_support_tag_content_iter_bracket_fence_compact_self_closing() -> Result<
 			.as_ref()
 			.and_then(|attrs| attrs.get("path"))
 			.ok_or("should extract the compact DELETE path attribute")?,
-		&"cache.txt"
+		"cache.txt"
 	);
 
 	Ok(())
@@ -89,9 +99,7 @@ fn test_support_tag_content_iter_attrs() -> Result<()> {
 	// -- Check
 	assert_eq!(tags.len(), 1);
 
-	let mut expected_attrs = HashMap::new();
-	expected_attrs.insert("path", "a/b.txt");
-	expected_attrs.insert("id", "123");
+	let expected_attrs = AttrsRef::from([("path", "a/b.txt"), ("id", "123")]);
 
 	assert_eq!(
 		tags[0],
@@ -102,6 +110,22 @@ fn test_support_tag_content_iter_attrs() -> Result<()> {
 			auto_closed: false,
 			start_idx: 7,
 			end_idx: 53,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(7, 35),
+				name: TagSpan::new(8, 12),
+				attrs: vec![
+					TagAttrSpan {
+						key: TagSpan::new(13, 17),
+						value: Some(TagSpan::new(19, 26)),
+					},
+					TagAttrSpan {
+						key: TagSpan::new(28, 30),
+						value: Some(TagSpan::new(31, 34)),
+					},
+				],
+				content: TagSpan::new(35, 47),
+				close_tag: Some(TagSpan::new(47, 54)),
+			},
 		}
 	);
 
@@ -122,9 +146,7 @@ fn test_support_tag_content_iter_attrs_with_newline() -> Result<()> {
 	// -- Check
 	assert_eq!(tags.len(), 1);
 
-	let mut expected_attrs = HashMap::new();
-	expected_attrs.insert("path", "a/b.txt");
-	expected_attrs.insert("id", "123");
+	let expected_attrs = AttrsRef::from([("path", "a/b.txt"), ("id", "123")]);
 
 	assert_eq!(
 		tags[0],
@@ -135,6 +157,22 @@ fn test_support_tag_content_iter_attrs_with_newline() -> Result<()> {
 			auto_closed: false,
 			start_idx: 7,
 			end_idx: 55,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(7, 37),
+				name: TagSpan::new(8, 12),
+				attrs: vec![
+					TagAttrSpan {
+						key: TagSpan::new(14, 18),
+						value: Some(TagSpan::new(20, 27)),
+					},
+					TagAttrSpan {
+						key: TagSpan::new(30, 32),
+						value: Some(TagSpan::new(33, 36)),
+					},
+				],
+				content: TagSpan::new(37, 49),
+				close_tag: Some(TagSpan::new(49, 56)),
+			},
 		}
 	);
 
@@ -163,11 +201,17 @@ fn test_support_tag_content_iter_multiple() -> Result<()> {
 			auto_closed: false,
 			start_idx: 6,
 			end_idx: 21,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(6, 12),
+				name: TagSpan::new(7, 11),
+				attrs: vec![],
+				content: TagSpan::new(12, 15),
+				close_tag: Some(TagSpan::new(15, 22)),
+			},
 		}
 	);
 
-	let mut expected_attrs = HashMap::new();
-	expected_attrs.insert("key", "val");
+	let expected_attrs = AttrsRef::from([("key", "val")]);
 
 	assert_eq!(
 		tags[1],
@@ -178,6 +222,16 @@ fn test_support_tag_content_iter_multiple() -> Result<()> {
 			auto_closed: false,
 			start_idx: 24,
 			end_idx: 47,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(24, 38),
+				name: TagSpan::new(25, 29),
+				attrs: vec![TagAttrSpan {
+					key: TagSpan::new(30, 33),
+					value: Some(TagSpan::new(34, 37)),
+				}],
+				content: TagSpan::new(38, 41),
+				close_tag: Some(TagSpan::new(41, 48)),
+			},
 		}
 	);
 
@@ -223,6 +277,13 @@ fn test_support_tag_content_iter_empty_content() -> Result<()> {
 			auto_closed: false,
 			start_idx: 0,
 			end_idx: 14,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(0, 7),
+				name: TagSpan::new(1, 6),
+				attrs: vec![],
+				content: TagSpan::new(7, 7),
+				close_tag: Some(TagSpan::new(7, 15)),
+			},
 		}
 	);
 
@@ -251,6 +312,13 @@ fn test_support_tag_content_iter_nested_like() -> Result<()> {
 			auto_closed: false,
 			start_idx: 0,
 			end_idx: 46,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(0, 7),
+				name: TagSpan::new(1, 6),
+				attrs: vec![],
+				content: TagSpan::new(7, 39),
+				close_tag: Some(TagSpan::new(39, 47)),
+			},
 		}
 	);
 
@@ -269,6 +337,13 @@ fn test_support_tag_content_iter_nested_like() -> Result<()> {
 			auto_closed: false,
 			start_idx: 13,
 			end_idx: 32,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(13, 20),
+				name: TagSpan::new(14, 19),
+				attrs: vec![],
+				content: TagSpan::new(20, 25),
+				close_tag: Some(TagSpan::new(25, 33)),
+			},
 		}
 	);
 
@@ -333,6 +408,13 @@ fn test_support_tag_content_iter_edges() -> Result<()> {
 			auto_closed: false,
 			start_idx: 0,
 			end_idx: 22,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(0, 7),
+				name: TagSpan::new(1, 6),
+				attrs: vec![],
+				content: TagSpan::new(7, 15),
+				close_tag: Some(TagSpan::new(15, 23)),
+			},
 		}
 	);
 
@@ -351,6 +433,13 @@ fn test_support_tag_content_iter_edges() -> Result<()> {
 			auto_closed: false,
 			start_idx: 29,
 			end_idx: 45,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(29, 34),
+				name: TagSpan::new(30, 33),
+				attrs: vec![],
+				content: TagSpan::new(34, 40),
+				close_tag: Some(TagSpan::new(40, 46)),
+			},
 		}
 	);
 
@@ -396,6 +485,13 @@ fn test_support_tag_content_iter_tag_name_prefix_check() -> Result<()> {
 			auto_closed: false,
 			start_idx: 28,
 			end_idx: 42,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(28, 33),
+				name: TagSpan::new(29, 32),
+				attrs: vec![],
+				content: TagSpan::new(33, 37),
+				close_tag: Some(TagSpan::new(37, 43)),
+			},
 		}
 	);
 
@@ -424,11 +520,17 @@ fn test_support_tag_content_iter_multiple_tag_names() -> Result<()> {
 			auto_closed: false,
 			start_idx: 6,
 			end_idx: 21,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(6, 11),
+				name: TagSpan::new(7, 10),
+				attrs: vec![],
+				content: TagSpan::new(11, 16),
+				close_tag: Some(TagSpan::new(16, 22)),
+			},
 		}
 	);
 
-	let mut expected_attrs = HashMap::new();
-	expected_attrs.insert("attr", "ok");
+	let expected_attrs = AttrsRef::from([("attr", "ok")]);
 
 	assert_eq!(
 		tags[1],
@@ -439,6 +541,16 @@ fn test_support_tag_content_iter_multiple_tag_names() -> Result<()> {
 			auto_closed: false,
 			start_idx: 28,
 			end_idx: 52,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(28, 41),
+				name: TagSpan::new(29, 32),
+				attrs: vec![TagAttrSpan {
+					key: TagSpan::new(33, 37),
+					value: Some(TagSpan::new(38, 40)),
+				}],
+				content: TagSpan::new(41, 47),
+				close_tag: Some(TagSpan::new(47, 53)),
+			},
 		}
 	);
 
@@ -617,6 +729,13 @@ fn test_support_tag_content_iter_self_closing_simple() -> Result<()> {
 			auto_closed: false,
 			start_idx: 0,
 			end_idx: 6,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(0, 7),
+				name: TagSpan::new(1, 5),
+				attrs: vec![],
+				content: TagSpan::new(7, 7),
+				close_tag: None,
+			},
 		}
 	);
 
@@ -637,9 +756,7 @@ fn test_support_tag_content_iter_self_closing_with_attrs() -> Result<()> {
 	// -- Check
 	assert_eq!(tags.len(), 1);
 
-	let mut expected_attrs = HashMap::new();
-	expected_attrs.insert("path", "a/b.txt");
-	expected_attrs.insert("id", "123");
+	let expected_attrs = AttrsRef::from([("path", "a/b.txt"), ("id", "123")]);
 
 	assert_eq!(
 		tags[0],
@@ -650,6 +767,22 @@ fn test_support_tag_content_iter_self_closing_with_attrs() -> Result<()> {
 			auto_closed: false,
 			start_idx: 0,
 			end_idx: 29,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(0, 30),
+				name: TagSpan::new(1, 5),
+				attrs: vec![
+					TagAttrSpan {
+						key: TagSpan::new(6, 10),
+						value: Some(TagSpan::new(12, 19)),
+					},
+					TagAttrSpan {
+						key: TagSpan::new(21, 23),
+						value: Some(TagSpan::new(24, 27)),
+					},
+				],
+				content: TagSpan::new(30, 30),
+				close_tag: None,
+			},
 		}
 	);
 
@@ -782,7 +915,7 @@ fn test_support_tag_content_iter_bracket_fence_shortened_self_closing() -> Resul
 			.as_ref()
 			.and_then(|attrs| attrs.get("path"))
 			.ok_or("should extract the DELETE path attribute")?,
-		&"temp.txt"
+		"temp.txt"
 	);
 
 	Ok(())
@@ -855,6 +988,13 @@ fn test_tag_ref_iter_auto_close_strict_and_malformed_candidates() -> Result<()>
 			auto_closed: true,
 			start_idx: 0,
 			end_idx: malformed_input.len() - 1,
+			spans: TagElemSpans {
+				open_tag: TagSpan::new(0, 6),
+				name: TagSpan::new(1, 5),
+				attrs: vec![],
+				content: TagSpan::new(6, 43),
+				close_tag: None,
+			},
 		})]
 	);
 
@@ -936,3 +1076,559 @@ fn test_tag_ref_iter_auto_close_empty_and_nested_configured_tags() -> Result<()>
 
 	Ok(())
 }
+
+#[test]
+fn test_tag_ref_iter_nested_same_name_depth() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<SECTION>a<SECTION>b</SECTION>c</SECTION> after";
+	let tag_names = ["SECTION"];
+
+	// -- Exec
+	let flat_parts: Vec<PartRef> =
+		TagRefIter::new(input, &tag_names, TagOptions::default().with_capture_text(true)).collect();
+	let nested_parts: Vec<PartRef> = TagRefIter::new(
+		input,
+		&tag_names,
+		TagOptions::default().with_capture_text(true).with_nested(true),
+	)
+	.collect();
+
+	// -- Check
+	assert_eq!(flat_parts.len(), 2);
+	assert_eq!(flat_parts[1], PartRef::Text("c</SECTION> after"));
+
+	assert_eq!(
+		nested_parts,
+		vec![
+			PartRef::TagElemRef(TagElemRef {
+				tag_name: "SECTION",
+				attrs: None,
+				content: "a<SECTION>b</SECTION>c",
+				auto_closed: false,
+				start_idx: 0,
+				end_idx: 40,
+				spans: TagElemSpans {
+					open_tag: TagSpan::new(0, 9),
+					name: TagSpan::new(1, 8),
+					attrs: vec![],
+					content: TagSpan::new(9, 31),
+					close_tag: Some(TagSpan::new(31, 41)),
+				},
+			}),
+			PartRef::Text(" after"),
+		]
+	);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_nested_ignores_self_closing_and_other_names() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<STEP id=1><STEP/><STEP_X><STEP id=2>x</STEP></STEP_X></STEP><STEP>last</STEP>";
+	let tag_names = ["STEP"];
+
+	// -- Exec
+	let parts: Vec<PartRef> = TagRefIter::new(input, &tag_names, TagOptions::default().with_nested(true)).collect();
+
+	// -- Check
+	let tags = extract_tag_elem_refs(parts);
+	assert_eq!(tags.len(), 2);
+	assert_eq!(tags[0].content, "<STEP/><STEP_X><STEP id=2>x</STEP></STEP_X>");
+	assert_eq!(tags[1].content, "last");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_nested_with_auto_close() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<PLAN><PLAN><FILE>a</FILE></PLAN> done <FILE>b</FILE>";
+	let tag_names = ["PLAN", "FILE"];
+
+	// -- Exec
+	let parts: Vec<PartRef> = TagRefIter::new(
+		input,
+		&tag_names,
+		TagOptions::default().with_nested(true).with_auto_close(true),
+	)
+	.collect();
+
+	// -- Check
+	let tags = extract_tag_elem_refs(parts);
+	assert_eq!(tags.len(), 2);
+	assert_eq!(tags[0].tag_name, "PLAN");
+	assert_eq!(tags[0].content, "<PLAN><FILE>a</FILE></PLAN> done ");
+	assert!(tags[0].auto_closed);
+	assert_eq!(tags[1].tag_name, "FILE");
+	assert_eq!(tags[1].content, "b");
+	assert!(!tags[1].auto_closed);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_wildcard_discovers_unknown_names() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"Intro <PLAN>p</PLAN> <NEW_DIRECTIVE mode="x">d</NEW_DIRECTIVE> 1 < 2 <ns:item/> <9BAD>no</9BAD>"#;
+
+	// -- Exec
+	let parts: Vec<PartRef> = TagRefIter::new(input, &[], TagOptions::default().with_wildcard(true)).collect();
+
+	// -- Check
+	let tags = extract_tag_elem_refs(parts);
+	let names: Vec<&str> = tags.iter().map(|tag| tag.tag_name).collect();
+	assert_eq!(names, ["PLAN", "NEW_DIRECTIVE", "ns:item"]);
+	assert_eq!(tags[1].content, "d");
+	assert_eq!(tags[1].attrs.as_ref().and_then(|attrs| attrs.get("mode")), Some("x"));
+	assert_eq!(tags[2].content, "");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_predicate_matcher_and_configured_names() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<FILE>a</FILE> <lower>b</lower> <UPPER_CASE>c</UPPER_CASE> <file>d</file>";
+	let matchers = [
+		TagNameMatcher::exact("file"),
+		TagNameMatcher::predicate(|name| name.bytes().all(|b| b.is_ascii_uppercase() || b == b'_')),
+	];
+
+	// -- Exec
+	let parts: Vec<PartRef> = TagRefIter::new_with_matchers(input, &matchers, None).collect();
+
+	// -- Check
+	let tags = extract_tag_elem_refs(parts);
+	let names: Vec<&str> = tags.iter().map(|tag| tag.tag_name).collect();
+	assert_eq!(names, ["FILE", "UPPER_CASE", "file"]);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_case_insensitive_names() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<file path=a>one</FILE> <File/> <FILEX>no</FILEX> <data>two</Data>";
+	let options = TagOptions::default().with_case_insensitive_names(true);
+
+	// -- Exec
+	let parts: Vec<PartRef> = TagRefIter::new(input, &["FILE", "DATA"], options).collect();
+
+	// -- Check
+	let tags = extract_tag_elem_refs(parts);
+	let names: Vec<&str> = tags.iter().map(|tag| tag.tag_name).collect();
+	assert_eq!(names, ["file", "File", "data"]);
+	let normalized_names: Vec<_> = tags.iter().map(|tag| tag.normalized_name()).collect();
+	assert_eq!(normalized_names, ["FILE", "FILE", "DATA"]);
+	assert_eq!(tags[0].content, "one");
+	assert_eq!(tags[2].content, "two");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_case_insensitive_names_disabled_by_default() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<file>one</FILE> <FILE>two</file> <FILE>three</FILE>";
+
+	// -- Exec
+	let parts: Vec<PartRef> = TagRefIter::new(input, &["FILE"], TagOptions::default()).collect();
+
+	// -- Check
+	let tags = extract_tag_elem_refs(parts);
+	assert_eq!(tags.len(), 1);
+	assert_eq!(tags[0].content, "two</file> <FILE>three");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_case_insensitive_names_nested() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<Step>a<STEP>b</step>c</STEP>";
+	let options = TagOptions::default().with_case_insensitive_names(true).with_nested(true);
+
+	// -- Exec
+	let parts: Vec<PartRef> = TagRefIter::new(input, &["step"], options).collect();
+
+	// -- Check
+	let tags = extract_tag_elem_refs(parts);
+	assert_eq!(tags.len(), 1);
+	assert_eq!(tags[0].tag_name, "Step");
+	assert_eq!(tags[0].content, "a<STEP>b</step>c");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_many_names_leftmost_longest() -> Result<()> {
+	// -- Setup & Fixtures
+	let generated_names: Vec<String> = (0..40).map(|i| format!("TOOL_{i}")).collect();
+	let mut tag_names: Vec<&str> = generated_names.iter().map(String::as_str).collect();
+	tag_names.extend(["A", "AB", "ABC"]);
+	let input = "<ABCD>no</ABCD> <AB>ab</AB> <ABC x=1>abc</ABC> <A>a</A> <TOOL_3>3</TOOL_3> <TOOL_39/>";
+
+	// -- Exec
+	let parts: Vec<PartRef> = TagRefIter::new(input, &tag_names, TagOptions::default()).collect();
+
+	// -- Check
+	let tags = extract_tag_elem_refs(parts);
+	let names: Vec<&str> = tags.iter().map(|tag| tag.tag_name).collect();
+	assert_eq!(names, ["AB", "ABC", "A", "TOOL_3", "TOOL_39"]);
+	let contents: Vec<&str> = tags.iter().map(|tag| tag.content).collect();
+	assert_eq!(contents, ["ab", "abc", "a", "3", ""]);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_auto_close_many_unclosed_elements() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<A>x</A> <B>y < z <A>1<B>2</B><A>3</A</A> <B>last";
+	let options = TagOptions::default().with_auto_close(true);
+
+	// -- Exec
+	let parts: Vec<PartRef> = TagRefIter::new(input, &["A", "B"], options).collect();
+
+	// -- Check
+	let tags = extract_tag_elem_refs(parts);
+	let summary: Vec<(&str, &str, bool)> =
+		tags.iter().map(|tag| (tag.tag_name, tag.content, tag.auto_closed)).collect();
+	assert_eq!(
+		summary,
+		[
+			("A", "x", false),
+			("B", "y < z ", true),
+			("A", "1", true),
+			("B", "2", false),
+			("A", "3</A", false),
+			("B", "last", true),
+		]
+	);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_recover_after_malformed_and_unclosed() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<A>1</A> <B>never closed <A>2</A> <B tail <A";
+	let options = TagOptions::default()
+		.with_capture_text(true)
+		.with_malformed_policy(MalformedPolicy::Recover);
+
+	// -- Exec
+	let parts: Vec<PartRef> = TagRefIter::new(input, &["A", "B"], options).collect();
+
+	// -- Check
+	assert_eq!(
+		parts,
+		vec![
+			PartRef::TagElemRef(TagElemRef {
+				tag_name: "A",
+				attrs: None,
+				content: "1",
+				auto_closed: false,
+				start_idx: 0,
+				end_idx: 7,
+				spans: TagElemSpans {
+					open_tag: TagSpan::new(0, 3),
+					name: TagSpan::new(1, 2),
+					attrs: vec![],
+					content: TagSpan::new(3, 4),
+					close_tag: Some(TagSpan::new(4, 8)),
+				},
+			}),
+			PartRef::Text(" <B>never closed "),
+			PartRef::TagElemRef(TagElemRef {
+				tag_name: "A",
+				attrs: None,
+				content: "2",
+				auto_closed: false,
+				start_idx: 25,
+				end_idx: 32,
+				spans: TagElemSpans {
+					open_tag: TagSpan::new(25, 28),
+					name: TagSpan::new(26, 27),
+					attrs: vec![],
+					content: TagSpan::new(28, 29),
+					close_tag: Some(TagSpan::new(29, 33)),
+				},
+			}),
+			PartRef::Text(" <B tail <A"),
+		]
+	);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_stop_diagnostics_skip_closes_after_stop() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "</B> <A>x <B>y</B>";
+
+	// -- Exec
+	let mut iter = TagRefIter::new(input, &["A", "B"], None).with_diagnostics();
+	let tags = extract_tag_elem_refs(iter.by_ref().collect());
+	let diagnostics = iter.into_diagnostics();
+
+	// -- Check
+	assert!(tags.is_empty());
+	let kinds: Vec<_> = diagnostics
+		.iter()
+		.map(|diagnostic| (diagnostic.kind, diagnostic.start_idx))
+		.collect();
+	assert_eq!(
+		kinds,
+		[(TagDiagnosticKind::OrphanClose, 0), (TagDiagnosticKind::Unclosed, 5)]
+	);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_recover_with_diagnostics() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<B>x <A>1</A> <A y <A>2</A> <B x";
+	let options = TagOptions::default().with_malformed_policy(MalformedPolicy::Recover);
+
+	// -- Exec
+	let mut iter = TagRefIter::new(input, &["A", "B"], options).with_diagnostics();
+	let tags = extract_tag_elem_refs(iter.by_ref().collect());
+	let diagnostics = iter.into_diagnostics();
+
+	// -- Check
+	let contents: Vec<&str> = tags.iter().map(|tag| tag.content).collect();
+	assert_eq!(contents, ["1", "2"]);
+	let kinds: Vec<_> = diagnostics
+		.iter()
+		.map(|diagnostic| (diagnostic.kind, diagnostic.start_idx))
+		.collect();
+	assert_eq!(
+		kinds,
+		[
+			(TagDiagnosticKind::Unclosed, 0),
+			(TagDiagnosticKind::MalformedOpen, 14),
+			(TagDiagnosticKind::MalformedOpen, 28)
+		]
+	);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_recover_repeated_unclosed_names() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<A>1 <B>2</B> <A>3 <A>4 <B>5</B>";
+	let options = TagOptions::default().with_malformed_policy(MalformedPolicy::Recover);
+
+	// -- Exec
+	let mut iter = TagRefIter::new(input, &["A", "B"], options).with_diagnostics();
+	let tags = extract_tag_elem_refs(iter.by_ref().collect());
+	let diagnostics = iter.into_diagnostics();
+
+	// -- Check
+	let contents: Vec<&str> = tags.iter().map(|tag| tag.content).collect();
+	assert_eq!(contents, ["2", "5"]);
+	let kinds: Vec<_> = diagnostics
+		.iter()
+		.map(|diagnostic| (diagnostic.kind, diagnostic.start_idx))
+		.collect();
+	assert_eq!(
+		kinds,
+		[
+			(TagDiagnosticKind::Unclosed, 0),
+			(TagDiagnosticKind::Unclosed, 14),
+			(TagDiagnosticKind::Unclosed, 19)
+		]
+	);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_recover_open_tag_interrupted_by_opening() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<FILE path=\"a.rs\"\nfn main() {}\n<DATA>x</DATA> <DATA>y</DATA>";
+	let options = TagOptions::default()
+		.with_capture_text(true)
+		.with_malformed_policy(MalformedPolicy::Recover);
+
+	// -- Exec
+	let mut iter = TagRefIter::new(input, &["FILE", "DATA"], options).with_diagnostics();
+	let parts: Vec<PartRef> = iter.by_ref().collect();
+	let diagnostics = iter.into_diagnostics();
+
+	// -- Check
+	assert_eq!(parts[0], PartRef::Text("<FILE path=\"a.rs\"\nfn main() {}\n"));
+	let tags = extract_tag_elem_refs(parts);
+	let contents: Vec<&str> = tags.iter().map(|tag| tag.content).collect();
+	assert_eq!(contents, ["x", "y"]);
+	let kinds: Vec<_> = diagnostics
+		.iter()
+		.map(|diagnostic| (diagnostic.kind, diagnostic.start_idx))
+		.collect();
+	assert_eq!(kinds, [(TagDiagnosticKind::MalformedOpen, 0)]);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_default_policy_keeps_open_tag_before_later_opening() -> Result<()> {
+	// -- Setup & Fixtures
+	let quoted_input = r#"<FILE title="use <DATA x">body</FILE>"#;
+	let unquoted_input = "<FILE a=1 <DATA>x</DATA>";
+
+	// -- Exec
+	let quoted_tags = extract_tag_elem_refs(TagRefIter::new(quoted_input, &["FILE", "DATA"], None).collect());
+	let auto_closed_tags = extract_tag_elem_refs(
+		TagRefIter::new(
+			unquoted_input,
+			&["FILE", "DATA"],
+			TagOptions::default().with_auto_close(true),
+		)
+		.collect(),
+	);
+
+	// -- Check
+	assert_eq!(quoted_tags.len(), 1);
+	assert_eq!(quoted_tags[0].content, "body");
+	assert_eq!(
+		quoted_tags[0].attrs.as_ref().and_then(|attrs| attrs.get("title")),
+		Some("use <DATA x")
+	);
+	assert_eq!(auto_closed_tags.len(), 1);
+	assert_eq!(auto_closed_tags[0].tag_name, "FILE");
+	assert_eq!(auto_closed_tags[0].content, "x</DATA>");
+	assert!(auto_closed_tags[0].auto_closed);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_recover_ignores_openings_in_quoted_values() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"<FILE title="use <DATA x">body</FILE> <NOTE text="a <b> c">n</NOTE>"#;
+	let options = TagOptions::default()
+		.with_wildcard(true)
+		.with_malformed_policy(MalformedPolicy::Recover);
+
+	// -- Exec
+	let mut iter = TagRefIter::new(input, &["FILE", "DATA"], options).with_diagnostics();
+	let tags = extract_tag_elem_refs(iter.by_ref().collect());
+	let diagnostics = iter.into_diagnostics();
+
+	// -- Check
+	let names: Vec<&str> = tags.iter().map(|tag| tag.tag_name).collect();
+	assert_eq!(names, ["FILE", "NOTE"]);
+	assert_eq!(tags[0].content, "body");
+	assert!(diagnostics.is_empty());
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_sub_spans_self_closing_and_auto_closed() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = r#"[[[DELETE path="x.rs" /]]] [[[FILE]]]body [[[FILE]]]last[[[/FILE]]]"#;
+	let options = TagOptions::default().with_fence(FENCE_BRACKETS).with_auto_close(true);
+
+	// -- Exec
+	let tags = extract_tag_elem_refs(TagRefIter::new(input, &["DELETE", "FILE"], options).collect());
+
+	// -- Check
+	assert_eq!(tags.len(), 3);
+	let text = |span: TagSpan| &input[span.range()];
+
+	let delete = &tags[0].spans;
+	assert_eq!(text(delete.open_tag), r#"[[[DELETE path="x.rs" /]]]"#);
+	assert_eq!(text(delete.name), "DELETE");
+	assert_eq!(delete.attrs.len(), 1);
+	assert_eq!(delete.attrs[0].value.map(text), Some("x.rs"));
+	assert!(delete.content.is_empty() && delete.content.start == delete.open_tag.end);
+	assert_eq!(delete.close_tag, None);
+
+	let auto_closed = &tags[1].spans;
+	assert_eq!(text(auto_closed.content), tags[1].content);
+	assert_eq!(auto_closed.close_tag, None);
+
+	let last = &tags[2].spans;
+	assert_eq!(text(last.open_tag), "[[[FILE]]]");
+	assert_eq!(text(last.content), "last");
+	assert_eq!(last.close_tag.map(text), Some("[[[/FILE]]]"));
+	assert_eq!(last.open_tag.start, tags[2].start_idx);
+	assert_eq!(last.close_tag.map(|close_tag| close_tag.end), Some(tags[2].end_idx + 1));
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_raw_sections() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<FILE>Use <![CDATA[<FILE>...</FILE>]]> as is.</FILE> <FILE><![CDATA[open</FILE>";
+	let fence = FENCE_XML.with_raw_section(RAW_SECTION_CDATA);
+	let options = TagOptions::default().with_fence(fence);
+
+	// -- Exec
+	let tags = extract_tag_elem_refs(TagRefIter::new(input, &["FILE"], options).collect());
+	let plain_tags = extract_tag_elem_refs(TagRefIter::new(input, &["FILE"], None).collect());
+
+	// -- Check
+	assert_eq!(tags.len(), 2);
+	assert_eq!(tags[0].content, "Use <![CDATA[<FILE>...</FILE>]]> as is.");
+	assert!(
+		matches!(tags[0].unwrapped_content(&fence), Cow::Owned(content) if content == "Use <FILE>...</FILE> as is.")
+	);
+	// Without its close delimiter, the raw section is plain text.
+	assert!(matches!(
+		tags[1].unwrapped_content(&fence),
+		Cow::Borrowed("<![CDATA[open")
+	));
+	assert_eq!(plain_tags[0].content, "Use <![CDATA[<FILE>...");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_terminator_attr() -> Result<()> {
+	// -- Setup & Fixtures
+	let input =
+		"<FILE path=a.rs end=\"@@END_A@@\">\nfn a() {} // </FILE> @@END_A@@\n@@END_A@@\nafter <FILE path=b.rs>b</FILE>";
+	let options = TagOptions::default().with_terminator_attr("end");
+
+	// -- Exec
+	let tags = extract_tag_elem_refs(TagRefIter::new(input, &["FILE"], options).collect());
+	let plain_tags = extract_tag_elem_refs(TagRefIter::new(input, &["FILE"], None).collect());
+
+	// -- Check
+	assert_eq!(tags.len(), 2);
+	assert_eq!(tags[0].content, "\nfn a() {} // </FILE> @@END_A@@\n");
+	let close_tag = tags[0].spans.close_tag.ok_or("should have a close tag span")?;
+	assert_eq!(&input[close_tag.range()], "@@END_A@@");
+	assert_eq!(tags[0].end_idx + 1, close_tag.end);
+	assert_eq!(tags[1].content, "b");
+	// Without the option, the first closing tag ends the element.
+	assert_eq!(plain_tags[0].content, "\nfn a() {} // ");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_ref_iter_terminator_attr_unterminated() -> Result<()> {
+	// -- Setup & Fixtures
+	let input = "<FILE end=EOF>\nabc\n</FILE>";
+	let options = TagOptions::default().with_terminator_attr("end");
+
+	// -- Exec
+	let stopped = extract_tag_elem_refs(TagRefIter::new(input, &["FILE"], options).collect());
+	let auto_closed = extract_tag_elem_refs(TagRefIter::new(input, &["FILE"], options.with_auto_close(true)).collect());
+
+	// -- Check
+	assert!(stopped.is_empty());
+	assert_eq!(auto_closed.len(), 1);
+	assert!(auto_closed[0].auto_closed);
+	assert_eq!(auto_closed[0].content, "\nabc\n</FILE>");
+
+	Ok(())
+}
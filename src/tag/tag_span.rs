@@ -0,0 +1,111 @@
+//! Byte spans of extracted elements and of their parts in the input.
+
+use crate::{LineIndex, TextPos};
+use serde::Serialize;
+use std::ops::Range;
+
+/// A half-open byte range `start..end` in the original input.
+///
+/// For an element, `start` is the `start_idx` of its opening tag, and `end` is one past its `end_idx`,
+/// so `&input[span.range()]` is the full element text.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TagSpan {
+	/// The byte index of the first character of the span.
+	pub start: usize,
+
+	/// The byte index just after the last character of the span.
+	pub end: usize,
+}
+
+/// The byte spans of the parts of an extracted element in the original input.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq, Default)]
+pub struct TagElemSpans {
+	/// The opening tag, from its open delimiter through its close delimiter (e.g., `<FILE path="a.rs">`).
+	pub open_tag: TagSpan,
+
+	/// The tag name in the opening tag.
+	pub name: TagSpan,
+
+	/// The attributes of the opening tag in source order, including duplicate keys.
+	pub attrs: Vec<TagAttrSpan>,
+
+	/// The content, empty just after the opening tag for a self-closing element.
+	pub content: TagSpan,
+
+	/// The closing tag, or `None` for a self-closing or auto-closed element.
+	pub close_tag: Option<TagSpan>,
+}
+
+/// The byte spans of an attribute of an opening tag.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct TagAttrSpan {
+	/// The attribute key.
+	pub key: TagSpan,
+
+	/// The attribute value, excluding its quotes, or `None` for an attribute without `=` (e.g., `readonly`).
+	pub value: Option<TagSpan>,
+}
+
+/// Constructors
+impl TagSpan {
+	pub fn new(start: usize, end: usize) -> Self {
+		TagSpan { start, end }
+	}
+}
+
+/// Accessors
+impl TagSpan {
+	pub fn range(self) -> Range<usize> {
+		self.start..self.end
+	}
+
+	pub fn len(self) -> usize {
+		self.end - self.start
+	}
+
+	pub fn is_empty(self) -> bool {
+		self.start == self.end
+	}
+
+	/// Returns the line and column of the first character of the span.
+	pub fn start_pos(self, line_index: &LineIndex) -> TextPos {
+		line_index.pos(self.start)
+	}
+
+	/// Returns the line and column just after the last character of the span.
+	pub fn end_pos(self, line_index: &LineIndex) -> TextPos {
+		line_index.pos(self.end)
+	}
+
+	/// Returns the span moved `offset` bytes further in the input.
+	pub(crate) fn shifted(self, offset: usize) -> Self {
+		TagSpan {
+			start: self.start + offset,
+			end: self.end + offset,
+		}
+	}
+}
+
+impl TagElemSpans {
+	/// Moves all spans `offset` bytes further in the input.
+	pub(crate) fn shift(&mut self, offset: usize) {
+		self.open_tag = self.open_tag.shifted(offset);
+		self.name = self.name.shifted(offset);
+		for attr in self.attrs.iter_mut() {
+			attr.key = attr.key.shifted(offset);
+			attr.value = attr.value.map(|value| value.shifted(offset));
+		}
+		self.content = self.content.shifted(offset);
+		self.close_tag = self.close_tag.map(|close_tag| close_tag.shifted(offset));
+	}
+}
+
+// region:    --- Froms
+
+impl From<Range<usize>> for TagSpan {
+	fn from(range: Range<usize>) -> Self {
+		TagSpan::new(range.start, range.end)
+	}
+}
+
+// endregion: --- Froms
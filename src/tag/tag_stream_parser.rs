@@ -0,0 +1,296 @@
+//! Incremental parser extracting tag elements from input received in chunks.
+#![doc = include_str!("../../docs/rustdoc/tag/tag_stream_parser.md")]
+
+use crate::tag::support::{ElemEnd, ElemEndSearch, OpenTag, TagScanner};
+use crate::tag::{Attrs, MalformedPolicy, Part, PartialTagElem, TagElem, TagElemRef, TagOptions, TagPattern};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// The parsing state of a `TagStreamParser` between two chunks.
+///
+/// All indices are byte offsets into the parser buffer.
+#[allow(clippy::large_enum_variant)] // A single state per parser, so boxing would only add an allocation per element.
+enum StreamState {
+	/// Searching for the next opening tag from `scan_idx`.
+	Text { scan_idx: usize },
+
+	/// Inside the element whose opening tag starts at `start_idx`, searching for its end.
+	Elem {
+		start_idx: usize,
+		tag_info: TagPattern,
+		open_tag: OpenTag,
+		search: ElemEndSearch,
+	},
+}
+
+impl StreamState {
+	/// Shifts all indices after `offset` bytes were removed from the start of the buffer.
+	fn shift(&mut self, offset: usize) {
+		match self {
+			StreamState::Text { scan_idx } => *scan_idx -= offset,
+			StreamState::Elem {
+				start_idx,
+				open_tag,
+				search,
+				..
+			} => {
+				*start_idx -= offset;
+				open_tag.close_start_idx -= offset;
+				open_tag.end_idx -= offset;
+				search.search_idx -= offset;
+				if let Some(raw_open_idx) = &mut search.raw_open_idx {
+					*raw_open_idx -= offset;
+				}
+				if let Some(line_start_idx) = &mut search.line_start_idx {
+					*line_start_idx -= offset;
+				}
+			}
+		}
+	}
+}
+
+/// A stateful parser that extracts `Part` values from input pushed in chunks, such as LLM token deltas.
+///
+/// It applies the same fence, auto-close, nesting, and text-capture rules as `TagRefIter`, and returns
+/// each part as soon as more input can no longer change it.
+pub struct TagStreamParser {
+	scanner: Arc<TagScanner>,
+	capture_text: bool,
+	malformed_policy: MalformedPolicy,
+	/// The received input that has not been returned as parts yet.
+	buffer: String,
+	/// The number of input bytes removed from the start of the buffer, to report element spans in the whole input.
+	drained_len: usize,
+	state: StreamState,
+}
+
+/// Constructors
+impl TagStreamParser {
+	/// Creates a new `TagStreamParser` for the given tag names.
+	///
+	/// # Arguments
+	///
+	/// * `tag_names` - The names of the tags to search for (e.g., &["FILE", "DATA"]).
+	/// * `options` - Parser configuration, or `None` for default options.
+	pub fn new(tag_names: &[&str], options: impl Into<TagOptions>) -> Self {
+		let options = options.into();
+		Self::new_with_scanner(Arc::new(TagScanner::new(tag_names, options)), options)
+	}
+
+	/// Creates a new `TagStreamParser` sharing an already built scanner (see `TagExtractor`).
+	pub(crate) fn new_with_scanner(scanner: Arc<TagScanner>, options: TagOptions) -> Self {
+		TagStreamParser {
+			scanner,
+			capture_text: options.capture_text(),
+			malformed_policy: options.malformed_policy,
+			buffer: String::new(),
+			drained_len: 0,
+			state: StreamState::Text { scan_idx: 0 },
+		}
+	}
+}
+
+/// Streaming
+impl TagStreamParser {
+	/// Appends a chunk of input and returns the parts completed by it.
+	///
+	/// Text is returned as soon as it cannot belong to a tag, so one text span of the input
+	/// may be split across several `Part::Text` values.
+	pub fn push(&mut self, chunk: &str) -> Vec<Part> {
+		self.buffer.push_str(chunk);
+		self.process(false)
+	}
+
+	/// Ends the input and returns the remaining parts.
+	///
+	/// Pending elements are resolved as if the pushed chunks were the whole input, so an unclosed
+	/// element is auto-closed when `auto_close` is enabled, or otherwise returned as text.
+	pub fn finish(mut self) -> Vec<Part> {
+		self.process(true)
+	}
+
+	/// Returns a snapshot of the element currently being received, if its opening tag is complete.
+	///
+	/// The snapshot holds the tag name, the parsed attributes, and the content received so far,
+	/// with `is_complete` set to `false`. Completed elements are returned by `push` instead.
+	///
+	/// The content borrows from the parser buffer, so taking a snapshot after every `push` does not copy it.
+	/// For the same reason, it keeps raw sections as written, while the completed element content is unwrapped.
+	pub fn partial_elem(&self) -> Option<PartialTagElem<'_>> {
+		let StreamState::Elem {
+			start_idx,
+			tag_info,
+			open_tag,
+			search,
+		} = &self.state
+		else {
+			return None;
+		};
+
+		// The content before the decided index cannot be part of the closing boundary.
+		let end = ElemEnd::Opening {
+			start_idx: search.decided_idx(),
+		};
+		let tag = self
+			.scanner
+			.build_elem_ref(&self.buffer, *start_idx, tag_info, open_tag, &end)?;
+
+		Some(PartialTagElem {
+			tag: tag.tag_name.to_string(),
+			attrs: tag.attrs.map(Attrs::from),
+			content: tag.content.into(),
+			is_complete: false,
+		})
+	}
+}
+
+/// Parsing support
+impl TagStreamParser {
+	fn process(&mut self, complete: bool) -> Vec<Part> {
+		let mut parts = Vec::new();
+		let input = self.buffer.as_str();
+		let scanner = &self.scanner;
+		let capture_text = self.capture_text;
+		let drained_len = self.drained_len;
+		let recover = self.malformed_policy == MalformedPolicy::Recover;
+		let push_text = |parts: &mut Vec<Part>, text: &str| {
+			if capture_text && !text.is_empty() {
+				parts.push(Part::Text(text.to_string()));
+			}
+		};
+		let push_elem = |parts: &mut Vec<Part>, tag: TagElemRef| {
+			let mut elem = TagElem::from_ref_in_fence(tag, scanner.fence());
+			elem.span = elem.span.shifted(drained_len);
+			parts.push(Part::TagElem(elem));
+		};
+
+		// Everything before `base` has been returned as parts.
+		let mut base = 0;
+		// The names found without a closing tag in the complete input (see `TagScanner::unclosed_name_key`).
+		let mut unclosed_names: HashSet<String> = HashSet::new();
+
+		loop {
+			let next_state = match &mut self.state {
+				StreamState::Text { scan_idx } => {
+					// --- Find the next decided opening tag, or return the text that precedes any candidate ---
+					let opening = scanner
+						.find_next_opening(input, *scan_idx, None)
+						.filter(|(start_idx, _)| scanner.is_decided(input, *start_idx, complete));
+					let Some((start_idx, tag_info)) = opening else {
+						let text_end = if complete {
+							input.len()
+						} else {
+							scanner.resume_idx(input, *scan_idx)
+						};
+						push_text(&mut parts, &input[base..text_end]);
+						base = text_end;
+						*scan_idx = text_end;
+						break;
+					};
+					push_text(&mut parts, &input[base..start_idx]);
+					base = start_idx;
+					*scan_idx = start_idx;
+
+					// --- Parse the opening tag ---
+					// With the recover policy, an opening tag interrupted by a later opening is malformed,
+					// which more input cannot change.
+					let open_tag = scanner.parse_open_tag(input, start_idx, &tag_info);
+					let interrupted = recover
+						&& open_tag.as_ref().is_some_and(|open_tag| {
+							scanner.is_interrupted_open_tag(input, start_idx, &tag_info, open_tag)
+						});
+					match open_tag.filter(|_| !interrupted) {
+						Some(open_tag) if scanner.is_decided(input, open_tag.close_start_idx, complete) => {
+							if open_tag.self_closing {
+								let Some(tag) =
+									scanner.build_elem_ref(input, start_idx, &tag_info, &open_tag, &ElemEnd::Eof)
+								else {
+									break;
+								};
+								base = tag.end_idx + 1;
+								push_elem(&mut parts, tag);
+								StreamState::Text { scan_idx: base }
+							} else {
+								let search = scanner.elem_end_search(input, start_idx, &tag_info, &open_tag);
+								StreamState::Elem {
+									start_idx,
+									tag_info: tag_info.into_owned(),
+									open_tag,
+									search,
+								}
+							}
+						}
+						// Malformed open tag, skipped as text with the recover policy once decided.
+						None if (complete || interrupted) && recover => {
+							*scan_idx = start_idx + tag_info.start_tag_prefix.len();
+							continue;
+						}
+						// Malformed open tag in the complete input, the rest is text (as with `TagRefIter`).
+						None if complete => {
+							push_text(&mut parts, &input[base..]);
+							base = input.len();
+							*scan_idx = base;
+							break;
+						}
+						_ => break,
+					}
+				}
+
+				StreamState::Elem {
+					start_idx,
+					tag_info,
+					open_tag,
+					search,
+				} => {
+					let unclosed_name = scanner.unclosed_name_key(tag_info, search);
+					let end = if complete && unclosed_name.is_some_and(|name| unclosed_names.contains(name)) {
+						ElemEnd::Unclosed
+					} else {
+						scanner.find_element_end(input, tag_info, search, complete)
+					};
+					if let (ElemEnd::Unclosed, Some(name)) = (&end, unclosed_name) {
+						unclosed_names.insert(name.to_string());
+					}
+					match end {
+						ElemEnd::Pending => break,
+						// Unclosed element in the complete input, skipped as text with the recover policy.
+						ElemEnd::Unclosed if recover => StreamState::Text {
+							scan_idx: open_tag.end_idx + 1,
+						},
+						// Unclosed element in the complete input, the rest is text (as with `TagRefIter`).
+						ElemEnd::Unclosed => {
+							push_text(&mut parts, &input[base..]);
+							base = input.len();
+							StreamState::Text { scan_idx: base }
+						}
+						_ => {
+							let Some(tag) = scanner.build_elem_ref(input, *start_idx, tag_info, open_tag, &end) else {
+								break;
+							};
+							base = tag.end_idx + 1;
+							push_elem(&mut parts, tag);
+							StreamState::Text { scan_idx: base }
+						}
+					}
+				}
+			};
+
+			self.state = next_state;
+		}
+
+		// -- Drop the returned input and rebase the state on the remaining buffer
+		self.buffer.drain(..base);
+		self.drained_len += base;
+		self.state.shift(base);
+
+		parts
+	}
+}
+
+// region:    --- Tests
+
+#[path = "tag_stream_parser_tests.rs"]
+#[cfg(test)]
+mod tests;
+
+// endregion: --- Tests
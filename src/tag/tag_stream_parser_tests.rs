@@ -0,0 +1,280 @@
+//! Tests for the TagStreamParser.
+
+use super::TagStreamParser;
+use crate::tag::{
+	FENCE_BRACKETS, FENCE_XML, MalformedPolicy, Part, PartialTagElem, RAW_SECTION_CDATA, TagOptions, extract,
+};
+
+type Result<T> = core::result::Result<T, Box<dyn std::error::Error>>;
+
+// -- Helpers
+
+/// Pushes `input` in chunks of `chunk_size` characters and collects every returned part.
+fn stream_parts(input: &str, chunk_size: usize, tag_names: &[&str], options: TagOptions) -> Vec<Part> {
+	let mut parser = TagStreamParser::new(tag_names, options);
+	let chars: Vec<char> = input.chars().collect();
+	let mut parts = Vec::new();
+	for chunk in chars.chunks(chunk_size) {
+		parts.extend(parser.push(&chunk.iter().collect::<String>()));
+	}
+	parts.extend(parser.finish());
+
+	merge_texts(parts)
+}
+
+/// Merges adjacent text parts, since the stream parser may split a text span.
+fn merge_texts(parts: Vec<Part>) -> Vec<Part> {
+	let mut merged: Vec<Part> = Vec::new();
+	for part in parts {
+		match (merged.last_mut(), part) {
+			(Some(Part::Text(last)), Part::Text(text)) => last.push_str(&text),
+			(_, part) => merged.push(part),
+		}
+	}
+	merged
+}
+
+#[test]
+fn test_tag_stream_parser_matches_extract_for_any_chunking() -> Result<()> {
+	// -- Setup & Fixtures
+	let cases: Vec<(&str, Vec<&str>, TagOptions)> = vec![
+		(
+			r#"Before <FILE path="a.rs">fn a() {}</FILE> middle <DATA/> <FILEX>no</FILEX> after"#,
+			vec!["FILE", "DATA"],
+			TagOptions::default().with_capture_text(true),
+		),
+		(
+			"Text [[[BIG_CONTENT path=\"/a.txt\"]]]\nbig\n[[[/BIG_CONTENT]]] and [[[DELETE path=x /]] end",
+			vec!["BIG_CONTENT", "DELETE"],
+			TagOptions::default().with_capture_text(true).with_fence(FENCE_BRACKETS),
+		),
+		(
+			"<FILE>first <DATA>second</DATA><FILE>unclosed",
+			vec!["FILE", "DATA"],
+			TagOptions::default().with_capture_text(true).with_auto_close(true),
+		),
+		(
+			"<S>a<S>b</S>c</S> tail <S/>",
+			vec!["S"],
+			TagOptions::default().with_capture_text(true).with_nested(true),
+		),
+		(
+			"ok <A>1</A> then <A>never closed",
+			vec!["A"],
+			TagOptions::default().with_capture_text(true),
+		),
+		(
+			"ok <A>1</A> then <A malformed",
+			vec!["A"],
+			TagOptions::default().with_capture_text(true),
+		),
+		("é <A>ü</A> ñ", vec!["A"], TagOptions::default()),
+		(
+			"<A>1</A> <B broken <A>2</A> <B>never closed <A>3</A> <B tail",
+			vec!["A", "B"],
+			TagOptions::default()
+				.with_capture_text(true)
+				.with_malformed_policy(MalformedPolicy::Recover),
+		),
+		(
+			"<A>1 <B>2</B> <A>3 <A>4 <B>5</B>",
+			vec!["A", "B"],
+			TagOptions::default()
+				.with_capture_text(true)
+				.with_malformed_policy(MalformedPolicy::Recover),
+		),
+		(
+			"<FILE title=\"use <DATA x\">body</FILE> <FILE a=1 <DATA>x</DATA>",
+			vec!["FILE", "DATA"],
+			TagOptions::default().with_capture_text(true).with_auto_close(true),
+		),
+		(
+			"<FILE path=\"a.rs\"\nfn main() {}\n<DATA>x</DATA> <DATA>y</DATA>",
+			vec!["FILE", "DATA"],
+			TagOptions::default()
+				.with_capture_text(true)
+				.with_malformed_policy(MalformedPolicy::Recover),
+		),
+		(
+			"x <file>a</FILE> <Data/> <FILE>b</file",
+			vec!["FILE", "DATA"],
+			TagOptions::default().with_capture_text(true).with_case_insensitive_names(true),
+		),
+		(
+			"a <UNKNOWN_DIRECTIVE id=1>x</UNKNOWN_DIRECTIVE> b <FILE>y</FILE> c <x-1/> d < e <lowercase",
+			vec!["FILE"],
+			TagOptions::default().with_capture_text(true).with_wildcard(true),
+		),
+		(
+			"<STEP>one <NOTE>n</NOTE><STEP>two<STEP_LONGER_NAME>three",
+			vec![],
+			TagOptions::default()
+				.with_capture_text(true)
+				.with_wildcard(true)
+				.with_auto_close(true),
+		),
+		(
+			"<FILE path=a><![CDATA[x </FILE> <DATA/>]]> y</FILE> <FILE><![CDATA[unterminated</FILE>",
+			vec!["FILE", "DATA"],
+			TagOptions::default()
+				.with_capture_text(true)
+				.with_fence(FENCE_XML.with_raw_section(RAW_SECTION_CDATA)),
+		),
+		(
+			"<FILE end=@@E@@>\nx </FILE> @@E@@\n  @@E@@ \r\n<DATA/>\n<FILE end=@@E@@>\nnever",
+			vec!["FILE", "DATA"],
+			TagOptions::default().with_capture_text(true).with_terminator_attr("end"),
+		),
+		(
+			"<FILE end=EOF>\none\nEOF\n<FILE end=EOF>\ntwo\nEOF",
+			vec!["FILE"],
+			TagOptions::default()
+				.with_capture_text(true)
+				.with_auto_close(true)
+				.with_terminator_attr("end"),
+		),
+	];
+
+	// -- Exec & Check
+	for (input, tag_names, options) in cases {
+		let expected = merge_texts(extract(input, &tag_names, options).into_parts());
+		for chunk_size in [1, 2, 3, 7, input.len()] {
+			let parts = stream_parts(input, chunk_size, &tag_names, options);
+			assert_eq!(parts, expected, "input {input:?} with chunk size {chunk_size}");
+		}
+	}
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_stream_parser_returns_parts_once_decided() -> Result<()> {
+	// -- Setup & Fixtures
+	let mut parser = TagStreamParser::new(&["FILE"], TagOptions::default().with_capture_text(true));
+
+	// -- Exec & Check
+	// The last 7 bytes (the length of `</FILE>`) are held back until they can be decided.
+	assert_eq!(parser.push("Hello <FI"), vec![Part::Text("He".to_string())]);
+	assert_eq!(parser.push("LE>content"), vec![Part::Text("llo ".to_string())]);
+	assert!(parser.push("</FI").is_empty());
+
+	let parts = parser.push("LE> bye");
+	assert_eq!(parts.len(), 1);
+	let Part::TagElem(elem) = &parts[0] else {
+		return Err("should complete the FILE element".into());
+	};
+	assert_eq!(elem.content, "content");
+
+	assert_eq!(parser.finish(), vec![Part::Text(" bye".to_string())]);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_stream_parser_partial_elem_snapshots() -> Result<()> {
+	// -- Setup & Fixtures
+	let mut parser = TagStreamParser::new(&["FILE"], TagOptions::default());
+
+	// -- Exec & Check
+	parser.push(r#"Writing <FILE path="src/ma"#);
+	assert_eq!(parser.partial_elem(), None);
+
+	parser.push("in.rs\">\nfn main() {\n    println!(\"hello\");\n}\n</FI");
+	let partial = parser.partial_elem().ok_or("should snapshot the FILE element in progress")?;
+	assert_eq!(partial.tag, "FILE");
+	assert_eq!(
+		partial.attrs.as_ref().and_then(|attrs| attrs.get("path")),
+		Some("src/main.rs")
+	);
+	assert!(!partial.is_complete);
+	assert!(partial.content.starts_with("\nfn main() {"));
+	assert!(!partial.content.contains("</FI"));
+
+	let parts = parser.push("LE> done");
+	assert_eq!(parser.partial_elem(), None);
+	let Some(Part::TagElem(elem)) = parts.into_iter().next() else {
+		return Err("should complete the FILE element".into());
+	};
+	let complete = PartialTagElem::from(elem);
+	assert!(complete.is_complete);
+	assert_eq!(complete.content, "\nfn main() {\n    println!(\"hello\");\n}\n");
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_stream_parser_long_raw_section_in_small_chunks() -> Result<()> {
+	// -- Setup & Fixtures
+	let body = "<FILE>x</FILE> ]] ".repeat(500);
+	let input = format!("<FILE><![CDATA[{body}]]></FILE> <FILE>after</FILE>");
+	let options = TagOptions::default().with_fence(FENCE_XML.with_raw_section(RAW_SECTION_CDATA));
+	let mut parser = TagStreamParser::new(&["FILE"], options);
+	let mut parts = Vec::new();
+	let mut pushed_len = 0;
+	let mut max_partial_lag = 0;
+
+	// -- Exec
+	for chunk in input.as_bytes().chunks(4) {
+		parts.extend(parser.push(std::str::from_utf8(chunk)?));
+		pushed_len += chunk.len();
+		if let Some(partial) = parser.partial_elem().filter(|_| parts.is_empty()) {
+			max_partial_lag = max_partial_lag.max(pushed_len - "<FILE>".len() - partial.content.len());
+		}
+	}
+	parts.extend(parser.finish());
+
+	// -- Check
+	assert_eq!(parts, extract(&input, &["FILE"], options).into_parts());
+	let Some(Part::TagElem(elem)) = parts.first() else {
+		return Err("should extract the FILE element with the raw section".into());
+	};
+	assert_eq!(elem.content, body);
+	// The snapshot content follows the raw section body received so far, holding back only undecided bytes.
+	assert!(
+		max_partial_lag <= 16,
+		"snapshot lags {max_partial_lag} bytes behind the input"
+	);
+
+	Ok(())
+}
+
+#[test]
+fn test_tag_stream_parser_long_terminator_lines_in_small_chunks() -> Result<()> {
+	// -- Setup & Fixtures
+	let line = format!("{} </FILE>\n", "x".repeat(2000));
+	let input = format!("<FILE end=@@END@@>\n{}@@END@@\n<FILE>after</FILE>", line.repeat(3));
+	let options = TagOptions::default().with_terminator_attr("end");
+	let mut parser = TagStreamParser::new(&["FILE"], options);
+	let mut parts = Vec::new();
+	let mut partial_contents = Vec::new();
+
+	// -- Exec
+	for chunk in input.as_bytes().chunks(3) {
+		parts.extend(parser.push(std::str::from_utf8(chunk)?));
+		if let Some(partial) = parser.partial_elem().filter(|_| parts.is_empty()) {
+			partial_contents.push(partial.content.into_owned());
+		}
+	}
+	parts.extend(parser.finish());
+
+	// -- Check
+	assert_eq!(parts, extract(&input, &["FILE"], options).into_parts());
+	let Some(Part::TagElem(elem)) = parts.first() else {
+		return Err("should extract the FILE element ending at its terminator line".into());
+	};
+	assert_eq!(elem.content, format!("\n{}", line.repeat(3)));
+	// The snapshot content holds the complete lines received so far, never the line that may be the terminator.
+	assert!(
+		partial_contents
+			.iter()
+			.all(|content| elem.content.starts_with(content.as_str()))
+	);
+	assert!(
+		partial_contents
+			.iter()
+			.all(|content| content.is_empty() || content.ends_with('\n'))
+	);
+	assert_eq!(partial_contents.last().map(String::as_str), Some(elem.content.as_str()));
+
+	Ok(())
+}
@@ -0,0 +1,121 @@
+//! Hierarchical, owned view of extracted tag elements.
+
+use crate::tag::{TagElem, TagElemNodeRef, TagFence, TagNodeRef, TagTreeRef};
+use serde::Serialize;
+
+/// A node of an extracted element tree, either plain text or an element with its children.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub enum TagNode {
+	/// Plain text content outside of any configured tag.
+	Text(String),
+
+	/// A tag element with its child nodes.
+	Elem(TagElemNode),
+}
+
+/// A tag element whose content is further split into child nodes.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct TagElemNode {
+	/// The element, whose `content` still holds the full inner markup.
+	pub elem: TagElem,
+
+	/// The text and elements found in the element content, in source order.
+	pub children: Vec<TagNode>,
+}
+
+/// Result of extracting an element tree from input.
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+pub struct TagTree {
+	pub(crate) nodes: Vec<TagNode>,
+}
+
+impl TagTree {
+	pub fn nodes(&self) -> &Vec<TagNode> {
+		&self.nodes
+	}
+
+	pub fn into_nodes(self) -> Vec<TagNode> {
+		self.nodes
+	}
+
+	pub fn iter(&self) -> std::slice::Iter<'_, TagNode> {
+		self.nodes.iter()
+	}
+}
+
+impl IntoIterator for TagTree {
+	type Item = TagNode;
+	type IntoIter = std::vec::IntoIter<Self::Item>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.nodes.into_iter()
+	}
+}
+
+impl<'a> IntoIterator for &'a TagTree {
+	type Item = &'a TagNode;
+	type IntoIter = std::slice::Iter<'a, TagNode>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.nodes.iter()
+	}
+}
+
+// region:    --- Froms
+
+impl From<TagNodeRef<'_>> for TagNode {
+	fn from(node_ref: TagNodeRef<'_>) -> Self {
+		match node_ref {
+			TagNodeRef::Text(text) => TagNode::Text(text.to_string()),
+			TagNodeRef::Elem(elem_node) => TagNode::Elem(TagElemNode::from(elem_node)),
+		}
+	}
+}
+
+impl From<TagElemNodeRef<'_>> for TagElemNode {
+	fn from(elem_node_ref: TagElemNodeRef<'_>) -> Self {
+		TagElemNode {
+			elem: TagElem::from(elem_node_ref.elem),
+			children: elem_node_ref.children.into_iter().map(TagNode::from).collect(),
+		}
+	}
+}
+
+impl From<TagTreeRef<'_>> for TagTree {
+	fn from(tree_ref: TagTreeRef<'_>) -> Self {
+		TagTree {
+			nodes: tree_ref.nodes.into_iter().map(TagNode::from).collect(),
+		}
+	}
+}
+
+/// Conversions
+impl TagTree {
+	/// Converts `tree_ref` like `From`, removing the delimiters of the raw sections of `fence` from element content.
+	pub(crate) fn from_ref_in_fence(tree_ref: TagTreeRef<'_>, fence: &TagFence) -> Self {
+		TagTree {
+			nodes: nodes_from_refs_in_fence(tree_ref.nodes, fence),
+		}
+	}
+}
+
+fn nodes_from_refs_in_fence(node_refs: Vec<TagNodeRef<'_>>, fence: &TagFence) -> Vec<TagNode> {
+	node_refs
+		.into_iter()
+		.map(|node_ref| match node_ref {
+			TagNodeRef::Text(text) => TagNode::Text(text.to_string()),
+			TagNodeRef::Elem(elem_node) => TagNode::Elem(TagElemNode {
+				elem: TagElem::from_ref_in_fence(elem_node.elem, fence),
+				children: nodes_from_refs_in_fence(elem_node.children, fence),
+			}),
+		})
+		.collect()
+}
+
+impl From<TagTree> for Vec<TagNode> {
+	fn from(val: TagTree) -> Self {
+		val.nodes
+	}
+}
+
+// endregion: --- Froms
@@ -0,0 +1,141 @@
+//! Hierarchical, zero-copy view of extracted tag elements.
+
+use crate::tag::support::TagScanner;
+use crate::tag::{PartRef, TagElemRef, TagOptions, TagRefIter};
+use std::sync::Arc;
+
+/// A node of an extracted element tree as a reference, either plain text or an element with its children.
+#[derive(Debug, PartialEq)]
+#[allow(clippy::large_enum_variant)] // Kept unboxed, like `PartRef`, so nodes match by value.
+pub enum TagNodeRef<'a> {
+	/// Plain text content outside of any configured tag.
+	Text(&'a str),
+
+	/// A tag element reference with its child nodes.
+	Elem(TagElemNodeRef<'a>),
+}
+
+/// A tag element reference whose content is further split into child nodes.
+#[derive(Debug, PartialEq)]
+pub struct TagElemNodeRef<'a> {
+	/// The element, with `start_idx` and `end_idx` relative to the original input.
+	pub elem: TagElemRef<'a>,
+
+	/// The text and elements found in the element content, in source order.
+	pub children: Vec<TagNodeRef<'a>>,
+}
+
+/// Result of extracting an element tree from input as references.
+#[derive(Debug, PartialEq, Default)]
+pub struct TagTreeRef<'a> {
+	pub(crate) nodes: Vec<TagNodeRef<'a>>,
+}
+
+impl<'a> TagTreeRef<'a> {
+	pub fn nodes(&self) -> &Vec<TagNodeRef<'a>> {
+		&self.nodes
+	}
+
+	pub fn into_nodes(self) -> Vec<TagNodeRef<'a>> {
+		self.nodes
+	}
+
+	pub fn iter(&self) -> std::slice::Iter<'_, TagNodeRef<'a>> {
+		self.nodes.iter()
+	}
+}
+
+impl<'a> IntoIterator for TagTreeRef<'a> {
+	type Item = TagNodeRef<'a>;
+	type IntoIter = std::vec::IntoIter<Self::Item>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.nodes.into_iter()
+	}
+}
+
+impl<'a, 'b> IntoIterator for &'b TagTreeRef<'a> {
+	type Item = &'b TagNodeRef<'a>;
+	type IntoIter = std::slice::Iter<'b, TagNodeRef<'a>>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.nodes.iter()
+	}
+}
+
+// region:    --- Support
+
+/// The maximum depth of element nodes in a tree. Deeper elements keep their content without child nodes,
+/// so untrusted, deeply nested input is not parsed again at every level.
+pub(crate) const MAX_TREE_DEPTH: usize = 64;
+
+/// An element whose content is being split into child nodes while building a tree.
+struct ContentFrame<'a> {
+	iter: TagRefIter<'a>,
+	/// The byte offset of the content in the original input.
+	offset: usize,
+	/// The element owning the content, or `None` for the top-level input.
+	elem: Option<TagElemRef<'a>>,
+	nodes: Vec<TagNodeRef<'a>>,
+}
+
+/// Extracts the nodes of `input`, splitting the content of each element into child nodes.
+///
+/// The tree is built with an explicit stack of content frames sharing one scanner, up to `MAX_TREE_DEPTH`.
+/// Within element content, the openings inside raw sections are text.
+pub(crate) fn build_nodes_ref<'a>(input: &'a str, tag_names: &[&str], options: TagOptions) -> Vec<TagNodeRef<'a>> {
+	let scanner = Arc::new(TagScanner::new(tag_names, options));
+	let mut stack = vec![ContentFrame {
+		iter: TagRefIter::new_with_scanner(input, scanner.clone(), options),
+		offset: 0,
+		elem: None,
+		nodes: Vec::new(),
+	}];
+
+	loop {
+		// The top-level frame holds the elements of depth 1.
+		let depth = stack.len();
+		let Some(frame) = stack.last_mut() else {
+			return Vec::new();
+		};
+		match frame.iter.next() {
+			Some(PartRef::Text(text)) => frame.nodes.push(TagNodeRef::Text(text)),
+			Some(PartRef::TagElemRef(mut elem)) => {
+				let offset = frame.offset;
+				let content = elem.content;
+				let content_offset = offset + elem.spans.content.start;
+				elem.start_idx += offset;
+				elem.end_idx += offset;
+				elem.spans.shift(offset);
+
+				if content.is_empty() || depth >= MAX_TREE_DEPTH {
+					let children = Vec::new();
+					frame.nodes.push(TagNodeRef::Elem(TagElemNodeRef { elem, children }));
+				} else {
+					let iter =
+						TagRefIter::new_with_scanner(content, scanner.clone(), options).with_raw_sections_skipped();
+					stack.push(ContentFrame {
+						iter,
+						offset: content_offset,
+						elem: Some(elem),
+						nodes: Vec::new(),
+					});
+				}
+			}
+			None => {
+				let Some(ContentFrame { elem, nodes, .. }) = stack.pop() else {
+					return Vec::new();
+				};
+				match (elem, stack.last_mut()) {
+					(Some(elem), Some(parent)) => {
+						let children = nodes;
+						parent.nodes.push(TagNodeRef::Elem(TagElemNodeRef { elem, children }));
+					}
+					_ => return nodes,
+				}
+			}
+		}
+	}
+}
+
+// endregion: --- Support